@@ -0,0 +1,102 @@
+use lava_top_rs::error::{BoxError, Locale};
+use lava_top_rs::secret::SecretString;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use url::Url;
+
+/// Переменная окружения с API ключом.
+pub const API_KEY_ENV: &str = "LAVA_TOP_API_KEY";
+/// Переменная окружения с базовым URL API.
+pub const BASE_URL_ENV: &str = "LAVA_TOP_BASE_URL";
+/// Переменная окружения с языком сообщений об ошибках (`en` или `ru`).
+pub const LANGUAGE_ENV: &str = "LAVA_TOP_LANGUAGE";
+
+/// Содержимое файла конфигурации (TOML).
+///
+/// ```toml
+/// api_key = "..."
+/// base_url = "https://gate.lava.top/"
+/// language = "ru"
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub struct Config {
+    pub api_key: Option<SecretString>,
+    pub base_url: Option<Url>,
+    /// Язык сообщений об ошибках; по умолчанию [`Locale::default`].
+    pub language: Option<Language>,
+}
+
+impl Config {
+    /// Язык сообщений об ошибках библиотеки.
+    pub fn locale(&self) -> Locale {
+        self.language.map_or_else(Locale::default, Locale::from)
+    }
+}
+
+/// Язык сообщений об ошибках в конфигурации.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    Ru,
+}
+
+impl FromStr for Language {
+    type Err = BoxError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "en" => Ok(Language::En),
+            "ru" => Ok(Language::Ru),
+            other => Err(format!("неизвестный язык {other:?}: ожидается en или ru").into()),
+        }
+    }
+}
+
+impl From<Language> for Locale {
+    fn from(language: Language) -> Self {
+        match language {
+            Language::En => Locale::En,
+            Language::Ru => Locale::Ru,
+        }
+    }
+}
+
+/// Путь к файлу конфигурации по умолчанию: `$XDG_CONFIG_HOME/lava-top/config.toml`
+/// или `~/.config/lava-top/config.toml`.
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("lava-top").join("config.toml"))
+}
+
+/// Загружает конфигурацию и применяет переменные окружения поверх файла.
+///
+/// Явно указанный файл обязан существовать; файл по умолчанию может отсутствовать.
+pub fn load(path: Option<&Path>) -> Result<Config, BoxError> {
+    let mut config = match path {
+        Some(path) => read(path)?,
+        None => match default_path() {
+            Some(path) if path.exists() => read(&path)?,
+            _ => Config::default(),
+        },
+    };
+    if let Ok(api_key) = std::env::var(API_KEY_ENV) {
+        config.api_key = Some(api_key.into());
+    }
+    if let Ok(base_url) = std::env::var(BASE_URL_ENV) {
+        config.base_url = Some(Url::parse(&base_url)?);
+    }
+    if let Ok(language) = std::env::var(LANGUAGE_ENV) {
+        config.language = Some(language.parse()?);
+    }
+    Ok(config)
+}
+
+fn read(path: &Path) -> Result<Config, BoxError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("не удалось прочитать {}: {e}", path.display()))?;
+    toml::from_str(&content).map_err(|e| format!("ошибка в {}: {e}", path.display()).into())
+}
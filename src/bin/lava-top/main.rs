@@ -0,0 +1,553 @@
+mod config;
+mod output;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
+use futures::TryStreamExt;
+use lava_top_rs::client::LavaTopClient;
+use lava_top_rs::error::{BoxError, LavaTopError};
+use lava_top_rs::models::common::{
+    ContractStatusDto, CurrencyDto, InvoiceStatus, InvoiceType, LanguageDto, PaymentMethod,
+    Periodicity, ProductType,
+};
+use lava_top_rs::models::invoice::{InvoiceRequest, InvoiceResponseV2, ListInvoicesParams};
+use lava_top_rs::models::product::{
+    FeedData, FeedItemCombined, ListProductsParams, ProductItemResponse, ProductUpdateRequest,
+    UpdateOfferRequest, UpdatePriceRequest,
+};
+use lava_top_rs::models::report::{ListPartnerProductSalesParams, ListPartnerSalesParams};
+use lava_top_rs::models::subscription::CancelSubscriptionParams;
+use output::{Format, Table};
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use url::Url;
+use uuid::Uuid;
+
+/// Командная строка для Lava Top API.
+///
+/// API ключ берется из переменной окружения LAVA_TOP_API_KEY или из файла конфигурации
+/// (по умолчанию ~/.config/lava-top/config.toml, поле api_key). Язык сообщений об ошибках
+/// задается переменной LAVA_TOP_LANGUAGE или полем language (en или ru).
+#[derive(Parser, Debug)]
+#[command(name = "lava-top", version)]
+struct Cli {
+    /// Формат вывода.
+    #[arg(long, short, value_enum, default_value_t, global = true)]
+    format: Format,
+    /// Путь к файлу конфигурации.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Базовый URL API (перекрывает конфигурацию).
+    #[arg(long, global = true)]
+    base_url: Option<Url>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Контракты API-ключа.
+    #[command(subcommand)]
+    Invoices(InvoicesCommand),
+    /// Создание контракта на покупку.
+    #[command(subcommand)]
+    Invoice(InvoiceCommand),
+    /// Отчеты о продажах.
+    #[command(subcommand)]
+    Sales(SalesCommand),
+    /// Продукты и офферы.
+    #[command(subcommand)]
+    Products(ProductsCommand),
+    /// Подписки покупателей.
+    #[command(subcommand)]
+    Subscription(SubscriptionCommand),
+    /// Донаты.
+    #[command(subcommand)]
+    Donate(DonateCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum InvoicesCommand {
+    /// Список контрактов.
+    List(ListInvoicesArgs),
+    /// Контракт по идентификатору.
+    Get {
+        /// Идентификатор контракта.
+        id: Uuid,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ListInvoicesArgs {
+    /// Почта покупателя.
+    #[arg(long)]
+    email: Option<String>,
+    /// Название продукта.
+    #[arg(long)]
+    product: Option<String>,
+    /// Последние 4 цифры карты.
+    #[arg(long)]
+    card: Option<String>,
+    /// Статусы контракта (NEW, IN_PROGRESS, COMPLETED, FAILED).
+    #[arg(long, value_delimiter = ',', value_parser = parse_invoice_status)]
+    status: Vec<InvoiceStatus>,
+    /// Типы контракта (ONE_TIME, RECURRING).
+    #[arg(long = "type", value_delimiter = ',', value_parser = parse_invoice_type)]
+    invoice_type: Vec<InvoiceType>,
+    /// Валюты (RUB, USD, EUR).
+    #[arg(long, value_delimiter = ',', value_parser = parse_currency)]
+    currency: Vec<CurrencyDto>,
+    /// Начало периода (YYYY-MM-DD или RFC 3339).
+    #[arg(long, value_parser = parse_datetime)]
+    from: Option<DateTime<Utc>>,
+    /// Конец периода (YYYY-MM-DD или RFC 3339).
+    #[arg(long, value_parser = parse_datetime)]
+    to: Option<DateTime<Utc>>,
+    #[command(flatten)]
+    page: PageArgs,
+}
+
+#[derive(Args, Debug)]
+struct PageArgs {
+    /// Номер страницы.
+    #[arg(long, conflicts_with = "all")]
+    page: Option<i64>,
+    /// Размер страницы.
+    #[arg(long)]
+    size: Option<i64>,
+    /// Загрузить все страницы.
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum InvoiceCommand {
+    /// Создает контракт и выводит ссылку на оплату.
+    Create {
+        /// Почта покупателя.
+        #[arg(long)]
+        email: String,
+        /// Идентификатор оффера.
+        #[arg(long)]
+        offer_id: Uuid,
+        /// Валюта покупки.
+        #[arg(long, value_parser = parse_currency, default_value = "RUB")]
+        currency: CurrencyDto,
+        /// Периодичность оплаты (для подписок).
+        #[arg(long, value_parser = parse_periodicity)]
+        periodicity: Option<Periodicity>,
+        /// Способ оплаты.
+        #[arg(long, value_parser = parse_payment_method)]
+        payment_method: Option<PaymentMethod>,
+        /// Язык покупателя.
+        #[arg(long, value_parser = parse_language)]
+        language: Option<LanguageDto>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SalesCommand {
+    /// Продажи по всем продуктам.
+    List(PageArgs),
+    /// Продажи конкретного продукта.
+    Product {
+        /// Идентификатор продукта.
+        product_id: Uuid,
+        /// Начало периода (YYYY-MM-DD).
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Конец периода (YYYY-MM-DD).
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Валюта.
+        #[arg(long, value_parser = parse_currency)]
+        currency: Option<CurrencyDto>,
+        /// Статус контракта (например, completed, subscription-active).
+        #[arg(long, value_parser = parse_contract_status)]
+        status: Option<ContractStatusDto>,
+        /// Строка поиска (например, почта покупателя).
+        #[arg(long)]
+        search: Option<String>,
+        #[command(flatten)]
+        page: PageArgs,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProductsCommand {
+    /// Все продукты с офферами и ценами.
+    List {
+        /// Тип продукта.
+        #[arg(long = "type", value_parser = parse_product_type)]
+        product_type: Option<ProductType>,
+        /// Показывать цены для всех периодов подписки.
+        #[arg(long)]
+        all_periods: bool,
+    },
+    /// Обновляет цены оффера.
+    UpdatePrice {
+        /// Идентификатор продукта.
+        product_id: Uuid,
+        /// Идентификатор оффера.
+        #[arg(long)]
+        offer_id: Uuid,
+        /// Цена в формате ВАЛЮТА=СУММА, например RUB=990. Можно указать несколько раз.
+        #[arg(long = "price", required = true, value_parser = parse_price)]
+        prices: Vec<UpdatePriceRequest>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SubscriptionCommand {
+    /// Отменяет подписку покупателя.
+    Cancel {
+        /// Идентификатор родительского контракта.
+        #[arg(long)]
+        contract_id: Uuid,
+        /// Почта покупателя.
+        #[arg(long)]
+        email: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum DonateCommand {
+    /// Ссылка на страницу доната.
+    Link,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let config = match config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("lava-top: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let locale = config.locale();
+    match run(cli, config).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            match e.downcast_ref::<LavaTopError>() {
+                Some(e) => eprintln!("lava-top: {}", e.localized(locale)),
+                None => eprintln!("lava-top: {e}"),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli, config: config::Config) -> Result<(), BoxError> {
+    let api_key = config.api_key.ok_or_else(|| {
+        format!(
+            "API ключ не задан: установите {} или api_key в файле конфигурации",
+            config::API_KEY_ENV
+        )
+    })?;
+    let mut builder = LavaTopClient::builder().api_key(api_key);
+    if let Some(base_url) = cli.base_url.or(config.base_url) {
+        builder = builder.base_url(base_url);
+    }
+    let client = builder.build()?;
+
+    let table = match cli.command {
+        Command::Invoices(InvoicesCommand::List(args)) => list_invoices(&client, args).await?,
+        Command::Invoices(InvoicesCommand::Get { id }) => {
+            invoices_table([client.get_invoice_by_id(&id).await?])
+        }
+        Command::Invoice(InvoiceCommand::Create {
+            email,
+            offer_id,
+            currency,
+            periodicity,
+            payment_method,
+            language,
+        }) => {
+            let mut request = InvoiceRequest::builder(email, offer_id).currency(currency);
+            if let Some(periodicity) = periodicity {
+                request = request.periodicity(periodicity);
+            }
+            if let Some(payment_method) = payment_method {
+                request = request.payment_method(payment_method);
+            }
+            if let Some(language) = language {
+                request = request.buyer_language(language);
+            }
+            let request = request.build()?;
+            let response = client.create_invoice_v2(&request).await?;
+            let mut table = Table::new(&["id", "status", "amount", "currency", "payment_url"]);
+            table.push(vec![
+                json!(response.id),
+                json!(response.status),
+                json!(response.amount_total.amount),
+                json!(response.amount_total.currency),
+                json!(response.payment_url),
+            ]);
+            table
+        }
+        Command::Sales(SalesCommand::List(page)) => {
+            let params = ListPartnerSalesParams {
+                page: page.page,
+                size: page.size,
+            };
+            let products = if page.all {
+                client.partner_sales_stream(params).try_collect().await?
+            } else {
+                client.list_partner_sales(Some(&params)).await?.items
+            };
+            let mut table = Table::new(&[
+                "product_id",
+                "title",
+                "status",
+                "currency",
+                "count",
+                "amount_total",
+            ]);
+            for product in products {
+                for sale in &product.sales {
+                    table.push(vec![
+                        json!(product.product_id),
+                        json!(product.title),
+                        json!(product.status),
+                        json!(sale.currency),
+                        json!(sale.count),
+                        json!(sale.amount_total),
+                    ]);
+                }
+            }
+            table
+        }
+        Command::Sales(SalesCommand::Product {
+            product_id,
+            from,
+            to,
+            currency,
+            status,
+            search,
+            page,
+        }) => {
+            let params = ListPartnerProductSalesParams {
+                page: page.page,
+                size: page.size,
+                from_date: from,
+                to_date: to,
+                currency,
+                status,
+                search,
+            };
+            let sales = if page.all {
+                client
+                    .partner_product_sales_stream(product_id, params)
+                    .try_collect()
+                    .await?
+            } else {
+                client
+                    .list_partner_product_sales(product_id, Some(&params))
+                    .await?
+                    .items
+            };
+            let mut table =
+                Table::new(&["id", "created_at", "status", "email", "amount", "currency"]);
+            for sale in sales {
+                table.push(vec![
+                    json!(sale.id),
+                    json!(sale.created_at),
+                    json!(sale.status),
+                    json!(sale.buyer.map(|b| b.email)),
+                    json!(sale.amount_total.as_ref().map(|a| a.amount)),
+                    json!(sale.amount_total.map(|a| a.currency)),
+                ]);
+            }
+            table
+        }
+        Command::Products(ProductsCommand::List {
+            product_type,
+            all_periods,
+        }) => {
+            let params = ListProductsParams {
+                product_types: product_type,
+                show_all_subscription_periods: all_periods.then_some(true),
+                ..Default::default()
+            };
+            let items: Vec<FeedItemCombined> = client.products_stream(params).try_collect().await?;
+            products_table(items.into_iter().filter_map(|item| match item.data {
+                FeedData::Product(product) => Some(product),
+                FeedData::Post(_) => None,
+            }))
+        }
+        Command::Products(ProductsCommand::UpdatePrice {
+            product_id,
+            offer_id,
+            prices,
+        }) => {
+            let request = ProductUpdateRequest {
+                offers: Some(vec![UpdateOfferRequest {
+                    id: offer_id,
+                    prices: Some(prices),
+                    name: None,
+                    description: None,
+                }]),
+            };
+            products_table([client.update_product(product_id, &request).await?])
+        }
+        Command::Subscription(SubscriptionCommand::Cancel { contract_id, email }) => {
+            let params = CancelSubscriptionParams { contract_id, email };
+            client.cancel_subscription(&params).await?;
+            let mut table = Table::new(&["contract_id", "result"]);
+            table.push(vec![json!(contract_id), json!("cancelled")]);
+            table
+        }
+        Command::Donate(DonateCommand::Link) => {
+            let response = client.get_donate_link().await?;
+            let mut table = Table::new(&["url"]);
+            table.push(vec![json!(response.url)]);
+            table
+        }
+    };
+
+    table.print(cli.format)?;
+    Ok(())
+}
+
+async fn list_invoices(client: &LavaTopClient, args: ListInvoicesArgs) -> Result<Table, BoxError> {
+    let params = ListInvoicesParams {
+        begin_date: args.from,
+        end_date: args.to,
+        buyer_email: args.email,
+        currencies: non_empty(args.currency),
+        last4_card_digits: args.card,
+        product_name: args.product,
+        invoice_types: non_empty(args.invoice_type),
+        invoice_statuses: non_empty(args.status),
+        page: args.page.page,
+        size: args.page.size,
+    };
+    let invoices: Vec<InvoiceResponseV2> = if args.page.all {
+        client.invoices_stream(params).try_collect().await?
+    } else {
+        client.list_invoices(Some(&params)).await?.items
+    };
+    Ok(invoices_table(invoices))
+}
+
+fn invoices_table(invoices: impl IntoIterator<Item = InvoiceResponseV2>) -> Table {
+    let mut table = Table::new(&[
+        "id",
+        "type",
+        "status",
+        "datetime",
+        "email",
+        "card_mask",
+        "product",
+        "offer",
+        "amount",
+        "currency",
+        "parent_id",
+        "subscription_status",
+    ]);
+    for invoice in invoices {
+        let buyer = invoice.buyer.as_ref();
+        let product = invoice.product.as_ref();
+        let receipt = invoice.receipt.as_ref();
+        table.push(vec![
+            json!(invoice.id),
+            json!(invoice.invoice_type),
+            json!(invoice.status),
+            json!(invoice.datetime),
+            json!(buyer.map(|b| &b.email)),
+            json!(buyer.and_then(|b| b.card_mask.as_ref())),
+            json!(product.and_then(|p| p.name.as_ref())),
+            json!(product.and_then(|p| p.offer.as_ref())),
+            json!(receipt.map(|r| r.amount)),
+            json!(receipt.map(|r| &r.currency)),
+            json!(invoice.parent_invoice.as_ref().map(|p| p.id)),
+            json!(invoice.subscription_status),
+        ]);
+    }
+    table
+}
+
+fn products_table(products: impl IntoIterator<Item = ProductItemResponse>) -> Table {
+    let mut table = Table::new(&[
+        "product_id",
+        "title",
+        "type",
+        "offer_id",
+        "offer",
+        "amount",
+        "currency",
+        "periodicity",
+    ]);
+    for product in products {
+        for offer in &product.offers {
+            for price in &offer.prices {
+                table.push(vec![
+                    json!(product.id),
+                    json!(product.title),
+                    json!(product.product_type),
+                    json!(offer.id),
+                    json!(offer.name),
+                    json!(price.amount),
+                    json!(price.currency),
+                    json!(price.periodicity),
+                ]);
+            }
+        }
+    }
+    table
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    (!values.is_empty()).then_some(values)
+}
+
+fn parse_datetime(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_err(|_| format!("ожидается дата YYYY-MM-DD или RFC 3339: {value}"))
+}
+
+fn parse_price(value: &str) -> Result<UpdatePriceRequest, String> {
+    let (currency, amount) = value
+        .split_once('=')
+        .ok_or_else(|| format!("ожидается ВАЛЮТА=СУММА: {value}"))?;
+    Ok(UpdatePriceRequest {
+        currency: parse_currency(currency)?,
+        amount: amount
+            .trim()
+            .parse()
+            .map_err(|_| format!("неверная сумма: {amount}"))?,
+    })
+}
+
+/// Разбирает значение перечисления API без учета регистра, отклоняя неизвестные значения.
+macro_rules! api_enum_parser {
+    ($name:ident, $ty:ty) => {
+        fn $name(value: &str) -> Result<$ty, String> {
+            [
+                value.to_string(),
+                value.to_uppercase(),
+                value.to_lowercase(),
+            ]
+            .iter()
+            .map(|candidate| <$ty>::from(candidate.as_str()))
+            .find(|parsed| !parsed.is_unknown())
+            .ok_or_else(|| format!("неизвестное значение: {value}"))
+        }
+    };
+}
+
+api_enum_parser!(parse_currency, CurrencyDto);
+api_enum_parser!(parse_periodicity, Periodicity);
+api_enum_parser!(parse_payment_method, PaymentMethod);
+api_enum_parser!(parse_language, LanguageDto);
+api_enum_parser!(parse_invoice_status, InvoiceStatus);
+api_enum_parser!(parse_invoice_type, InvoiceType);
+api_enum_parser!(parse_contract_status, ContractStatusDto);
+api_enum_parser!(parse_product_type, ProductType);
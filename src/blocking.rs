@@ -0,0 +1,303 @@
+use crate::client::{LavaTopClientBuilder, WaitPolicy};
+use crate::error::{ConfigError, LavaTopError};
+use crate::models::common::PagedResponseV2;
+use crate::models::donate::DonateResponse;
+use crate::models::feed::{FeedPageResponse, GetFeedParams};
+use crate::models::invoice::{
+    InvoicePageResponse, InvoicePaymentParamsResponse, InvoiceRequestDto, InvoiceResponseV2,
+    ListInvoicesParams,
+};
+use crate::models::product::{
+    FeedItemCombined, ListProductsParams, ProductItemResponse, ProductUpdateRequest,
+};
+use crate::models::report::{
+    ListPartnerProductSalesParams, ListPartnerSalesParams, PartnerProductDto,
+    PartnerProductSalesPageResponse, PartnerSaleDetailsDto, PartnerSalesPageResponse,
+};
+use crate::models::subscription::CancelSubscriptionParams;
+use futures::stream::{BoxStream, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use url::Url;
+use uuid::Uuid;
+
+/// Синхронный клиент для Lava Top API, по аналогии с `reqwest::blocking`.
+///
+/// Оборачивает асинхронный [`LavaTopClient`](crate::client::LavaTopClient) и выполняет
+/// запросы на собственном однопоточном runtime tokio. Модели и ошибки общие с асинхронным
+/// клиентом. Клоны разделяют runtime и пул соединений.
+///
+/// # Panics
+///
+/// Методы клиента паникуют при вызове внутри асинхронного контекста (например, из
+/// `#[tokio::main]`). Там используйте асинхронный клиент. Удалять клиент можно где угодно:
+/// runtime останавливается в фоне, не блокируя текущий поток.
+///
+/// ```no_run
+/// # use lava_top_rs::blocking::LavaTopClient;
+/// # fn main() -> Result<(), lava_top_rs::error::LavaTopError> {
+/// let client = LavaTopClient::new("my-api-key".to_string(), None)?;
+/// let invoice = client.get_invoice_by_id(&uuid::Uuid::nil())?;
+/// println!("{}", invoice.status);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LavaTopClient {
+    inner: crate::client::LavaTopClient,
+    runtime: Arc<BlockingRuntime>,
+}
+
+/// Runtime синхронного клиента.
+///
+/// Обычное удаление `Runtime` ждет его задачи и паникует внутри асинхронного контекста,
+/// поэтому runtime останавливается через `shutdown_background`.
+struct BlockingRuntime(Option<Runtime>);
+
+impl BlockingRuntime {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0
+            .as_ref()
+            .expect("runtime удаляется только в drop")
+            .block_on(future)
+    }
+}
+
+impl Drop for BlockingRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl fmt::Debug for LavaTopClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LavaTopClient")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LavaTopClient {
+    /// Создает новый экземпляр клиента.
+    ///
+    /// Для тонкой настройки используйте [`LavaTopClient::builder`] и
+    /// [`LavaTopClientBuilder::build_blocking`].
+    pub fn new(api_key: String, base_url: Option<Url>) -> Result<Self, LavaTopError> {
+        let mut builder = Self::builder().api_key(api_key);
+        if let Some(url) = base_url {
+            builder = builder.base_url(url);
+        }
+        builder.build_blocking()
+    }
+
+    /// Построитель клиента; завершите его вызовом [`LavaTopClientBuilder::build_blocking`].
+    pub fn builder() -> LavaTopClientBuilder {
+        LavaTopClientBuilder::new()
+    }
+
+    /// Оборачивает готовый асинхронный клиент.
+    pub fn from_async(inner: crate::client::LavaTopClient) -> Result<Self, LavaTopError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(ConfigError::Runtime)?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(BlockingRuntime(Some(runtime))),
+        })
+    }
+
+    /// Асинхронный клиент, на котором построен этот.
+    pub fn as_async(&self) -> &crate::client::LavaTopClient {
+        &self.inner
+    }
+
+    /// Устанавливает новый базовый URL для клиента.
+    pub fn set_base_url(&mut self, url: Url) {
+        self.inner.set_base_url(url);
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    fn iter<T>(
+        &self,
+        stream: impl Stream<Item = Result<T, LavaTopError>> + Send + 'static,
+    ) -> Iter<T> {
+        Iter {
+            stream: stream.boxed(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// См. [`crate::client::LavaTopClient::get_feed`].
+    #[deprecated(note = "Эндпоинт /api/v1/feed устарел. Используйте list_products_v2.")]
+    #[allow(deprecated)]
+    pub fn get_feed(
+        &self,
+        params: Option<&GetFeedParams>,
+    ) -> Result<FeedPageResponse, LavaTopError> {
+        self.block_on(self.inner.get_feed(params))
+    }
+
+    /// См. [`crate::client::LavaTopClient::create_invoice_v1`].
+    #[deprecated(note = "Эндпоинт POST /api/v1/invoice устарел. Используйте create_invoice_v2.")]
+    #[allow(deprecated)]
+    pub fn create_invoice_v1(
+        &self,
+        request: &InvoiceRequestDto,
+    ) -> Result<InvoicePaymentParamsResponse, LavaTopError> {
+        self.block_on(self.inner.create_invoice_v1(request))
+    }
+
+    /// См. [`crate::client::LavaTopClient::create_invoice_v2`].
+    pub fn create_invoice_v2(
+        &self,
+        request: &InvoiceRequestDto,
+    ) -> Result<InvoicePaymentParamsResponse, LavaTopError> {
+        self.block_on(self.inner.create_invoice_v2(request))
+    }
+
+    /// См. [`crate::client::LavaTopClient::list_invoices`].
+    pub fn list_invoices(
+        &self,
+        params: Option<&ListInvoicesParams>,
+    ) -> Result<InvoicePageResponse, LavaTopError> {
+        self.block_on(self.inner.list_invoices(params))
+    }
+
+    /// См. [`crate::client::LavaTopClient::get_invoice_by_id`].
+    pub fn get_invoice_by_id(&self, invoice_id: &Uuid) -> Result<InvoiceResponseV2, LavaTopError> {
+        self.block_on(self.inner.get_invoice_by_id(invoice_id))
+    }
+
+    /// См. [`crate::client::LavaTopClient::list_partner_sales`].
+    pub fn list_partner_sales(
+        &self,
+        params: Option<&ListPartnerSalesParams>,
+    ) -> Result<PartnerSalesPageResponse, LavaTopError> {
+        self.block_on(self.inner.list_partner_sales(params))
+    }
+
+    /// См. [`crate::client::LavaTopClient::list_partner_product_sales`].
+    pub fn list_partner_product_sales(
+        &self,
+        product_id: Uuid,
+        params: Option<&ListPartnerProductSalesParams>,
+    ) -> Result<PartnerProductSalesPageResponse, LavaTopError> {
+        self.block_on(self.inner.list_partner_product_sales(product_id, params))
+    }
+
+    /// См. [`crate::client::LavaTopClient::cancel_subscription`].
+    pub fn cancel_subscription(
+        &self,
+        params: &CancelSubscriptionParams,
+    ) -> Result<(), LavaTopError> {
+        self.block_on(self.inner.cancel_subscription(params))
+    }
+
+    /// См. [`crate::client::LavaTopClient::list_products_v2`].
+    pub fn list_products_v2(
+        &self,
+        params: Option<&ListProductsParams>,
+    ) -> Result<PagedResponseV2<FeedItemCombined>, LavaTopError> {
+        self.block_on(self.inner.list_products_v2(params))
+    }
+
+    /// См. [`crate::client::LavaTopClient::update_product`].
+    pub fn update_product(
+        &self,
+        product_id: Uuid,
+        request: &ProductUpdateRequest,
+    ) -> Result<ProductItemResponse, LavaTopError> {
+        self.block_on(self.inner.update_product(product_id, request))
+    }
+
+    /// См. [`crate::client::LavaTopClient::get_donate_link`].
+    pub fn get_donate_link(&self) -> Result<DonateResponse, LavaTopError> {
+        self.block_on(self.inner.get_donate_link())
+    }
+
+    /// См. [`crate::client::LavaTopClient::fetch_next_page`].
+    pub fn fetch_next_page<T: DeserializeOwned>(
+        &self,
+        next_page: &Url,
+    ) -> Result<PagedResponseV2<T>, LavaTopError> {
+        self.block_on(self.inner.fetch_next_page(next_page))
+    }
+
+    /// См. [`crate::client::LavaTopClient::wait_for_invoice`].
+    pub fn wait_for_invoice(
+        &self,
+        invoice_id: Uuid,
+        policy: &WaitPolicy,
+    ) -> Result<InvoiceResponseV2, LavaTopError> {
+        self.block_on(self.inner.wait_for_invoice(invoice_id, policy))
+    }
+
+    /// Итератор по всем контрактам, см. [`crate::client::LavaTopClient::invoices_stream`].
+    pub fn invoices_iter(&self, params: ListInvoicesParams) -> Iter<InvoiceResponseV2> {
+        self.iter(self.inner.invoices_stream(params))
+    }
+
+    /// Итератор по всем продажам партнёра, см. [`crate::client::LavaTopClient::partner_sales_stream`].
+    pub fn partner_sales_iter(&self, params: ListPartnerSalesParams) -> Iter<PartnerProductDto> {
+        self.iter(self.inner.partner_sales_stream(params))
+    }
+
+    /// Итератор по продажам продукта, см. [`crate::client::LavaTopClient::partner_product_sales_stream`].
+    pub fn partner_product_sales_iter(
+        &self,
+        product_id: Uuid,
+        params: ListPartnerProductSalesParams,
+    ) -> Iter<PartnerSaleDetailsDto> {
+        self.iter(self.inner.partner_product_sales_stream(product_id, params))
+    }
+
+    /// Итератор по всем продуктам и постам, см. [`crate::client::LavaTopClient::products_stream`].
+    pub fn products_iter(&self, params: ListProductsParams) -> Iter<FeedItemCombined> {
+        self.iter(self.inner.products_stream(params))
+    }
+}
+
+/// Итератор по элементам всех страниц; следующая страница запрашивается по мере чтения.
+pub struct Iter<T> {
+    stream: BoxStream<'static, Result<T, LavaTopError>>,
+    runtime: Arc<BlockingRuntime>,
+}
+
+impl<T> fmt::Debug for Iter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter").finish_non_exhaustive()
+    }
+}
+
+impl<T> Iterator for Iter<T> {
+    type Item = Result<T, LavaTopError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_inside_async_context_does_not_panic() {
+        let client = LavaTopClient::new("key".to_string(), None).unwrap();
+        let clone = client.clone();
+        drop(client);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        // Последний клон удаляется внутри runtime другого клиента
+        runtime.block_on(async move { drop(clone) });
+    }
+}
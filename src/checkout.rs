@@ -0,0 +1,434 @@
+use crate::client::LavaTopClient;
+use crate::error::{FieldIssue, LavaTopError, ValidationErrors};
+use crate::models::common::{
+    AmountTotalDto, ClientUtmDto, ContractStatusDto, CurrencyDto, LanguageDto, PaymentMethod,
+    Periodicity, PriceDto,
+};
+use crate::models::invoice::InvoiceRequest;
+use crate::models::product::{FeedData, ListProductsParams, OfferResponse, ProductItemResponse};
+use futures::TryStreamExt;
+use futures::lock::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use url::Url;
+use uuid::Uuid;
+
+/// Время жизни каталога по умолчанию.
+const DEFAULT_CATALOG_TTL: Duration = Duration::from_secs(300);
+
+/// Не перезагружать каталог из-за промаха чаще, чем раз в этот интервал.
+const MIN_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Продукт в каталоге: по идентификатору или по точному названию.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProductRef {
+    Id(Uuid),
+    /// Название сравнивается без учета регистра и пробелов по краям.
+    Title(String),
+}
+
+impl From<Uuid> for ProductRef {
+    fn from(id: Uuid) -> Self {
+        ProductRef::Id(id)
+    }
+}
+
+impl From<&str> for ProductRef {
+    fn from(title: &str) -> Self {
+        ProductRef::Title(title.to_string())
+    }
+}
+
+impl From<String> for ProductRef {
+    fn from(title: String) -> Self {
+        ProductRef::Title(title)
+    }
+}
+
+/// Покупка, которую нужно оформить через [`Checkout::create`].
+#[derive(Debug, Clone)]
+pub struct CheckoutRequest {
+    email: String,
+    product: ProductRef,
+    offer_name: String,
+    currency: CurrencyDto,
+    periodicity: Option<Periodicity>,
+    payment_method: Option<PaymentMethod>,
+    buyer_language: Option<LanguageDto>,
+    client_utm: Option<ClientUtmDto>,
+}
+
+impl CheckoutRequest {
+    /// Покупка оффера `offer_name` продукта `product` покупателем `email`, по умолчанию в рублях.
+    pub fn new(
+        email: impl Into<String>,
+        product: impl Into<ProductRef>,
+        offer_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            email: email.into(),
+            product: product.into(),
+            offer_name: offer_name.into(),
+            currency: CurrencyDto::default(),
+            periodicity: None,
+            payment_method: None,
+            buyer_language: None,
+            client_utm: None,
+        }
+    }
+
+    /// Валюта покупки.
+    pub fn currency(mut self, currency: CurrencyDto) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    /// Периодичность оплаты. Если не задана, выбирается разовая цена.
+    pub fn periodicity(mut self, periodicity: Periodicity) -> Self {
+        self.periodicity = Some(periodicity);
+        self
+    }
+
+    /// Способ оплаты.
+    pub fn payment_method(mut self, payment_method: PaymentMethod) -> Self {
+        self.payment_method = Some(payment_method);
+        self
+    }
+
+    /// Язык покупателя для нотификаций.
+    pub fn buyer_language(mut self, language: LanguageDto) -> Self {
+        self.buyer_language = Some(language);
+        self
+    }
+
+    /// UTM-метки покупки.
+    pub fn client_utm(mut self, utm: ClientUtmDto) -> Self {
+        self.client_utm = Some(utm);
+        self
+    }
+}
+
+/// Цена, найденная в каталоге.
+#[derive(Debug, Clone)]
+pub struct ResolvedPrice {
+    pub product_id: Uuid,
+    pub offer: OfferResponse,
+    pub price: PriceDto,
+}
+
+impl ResolvedPrice {
+    /// Периодичность цены; цена без периодичности считается разовой.
+    pub fn periodicity(&self) -> Periodicity {
+        self.price.periodicity_or_one_time()
+    }
+
+    /// Сумма к оплате по данным каталога, если у цены указана сумма.
+    pub fn expected_amount(&self) -> Option<AmountTotalDto> {
+        self.price.amount.map(|amount| AmountTotalDto {
+            currency: self.price.currency.clone(),
+            amount,
+        })
+    }
+}
+
+/// Результат [`Checkout::create`].
+#[derive(Debug, Clone)]
+pub struct CheckoutSession {
+    /// Идентификатор созданного контракта.
+    pub contract_id: Uuid,
+    pub status: ContractStatusDto,
+    /// Ссылка на виджет оплаты.
+    pub payment_url: Option<Url>,
+    /// Сумма к оплате по данным каталога.
+    pub expected_amount: Option<AmountTotalDto>,
+    /// Сумма к оплате, которую вернул API при создании контракта.
+    pub amount_total: AmountTotalDto,
+    pub price: ResolvedPrice,
+}
+
+#[derive(Debug)]
+struct CachedCatalog {
+    products: Arc<Vec<ProductItemResponse>>,
+    loaded_at: Instant,
+}
+
+/// Оформление покупок по названиям продуктов и офферов.
+///
+/// Находит в каталоге продуктов (`list_products_v2`) оффер и цену в нужной валюте и
+/// с нужной периодичностью, затем создает контракт через `create_invoice_v2`. Каталог
+/// кэшируется на [`Checkout::ttl`] и разделяется между клонами; если продукт или оффер
+/// не найден, каталог перезагружается (не чаще раза в 10 секунд).
+///
+/// ```no_run
+/// # use lava_top_rs::checkout::{Checkout, CheckoutRequest};
+/// # use lava_top_rs::client::LavaTopClient;
+/// # use lava_top_rs::models::common::{CurrencyDto, Periodicity};
+/// # async fn run(client: LavaTopClient) -> Result<(), lava_top_rs::error::LavaTopError> {
+/// let checkout = Checkout::new(client);
+/// let session = checkout
+///     .create(
+///         &CheckoutRequest::new("buyer@example.com", "Курс по Rust", "Базовый")
+///             .currency(CurrencyDto::Usd)
+///             .periodicity(Periodicity::Monthly),
+///     )
+///     .await?;
+/// println!("{:?} {:?}", session.payment_url, session.expected_amount);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Checkout {
+    client: LavaTopClient,
+    ttl: Duration,
+    catalog: Arc<Mutex<Option<CachedCatalog>>>,
+}
+
+impl Checkout {
+    pub fn new(client: LavaTopClient) -> Self {
+        Self {
+            client,
+            ttl: DEFAULT_CATALOG_TTL,
+            catalog: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Время жизни закэшированного каталога. По умолчанию 5 минут.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Продукты каталога; загружаются заново, если кэш устарел.
+    pub async fn catalog(&self) -> Result<Arc<Vec<ProductItemResponse>>, LavaTopError> {
+        self.load_catalog(false).await
+    }
+
+    /// Сбрасывает кэш: следующий запрос загрузит каталог заново.
+    pub async fn invalidate(&self) {
+        *self.catalog.lock().await = None;
+    }
+
+    /// Находит цену оффера в каталоге.
+    pub async fn resolve(
+        &self,
+        product: &ProductRef,
+        offer_name: &str,
+        currency: &CurrencyDto,
+        periodicity: Option<&Periodicity>,
+    ) -> Result<ResolvedPrice, LavaTopError> {
+        let catalog = self.load_catalog(false).await?;
+        match find_price(&catalog, product, offer_name, currency, periodicity) {
+            Err(Lookup::Missing(_)) => {
+                // Продукт или оффер мог появиться после загрузки каталога
+                let catalog = self.load_catalog(true).await?;
+                find_price(&catalog, product, offer_name, currency, periodicity)
+                    .map_err(Lookup::into_error)
+            }
+            result => result.map_err(Lookup::into_error),
+        }
+    }
+
+    /// Находит цену и создает контракт.
+    pub async fn create(&self, request: &CheckoutRequest) -> Result<CheckoutSession, LavaTopError> {
+        let price = self
+            .resolve(
+                &request.product,
+                &request.offer_name,
+                &request.currency,
+                request.periodicity.as_ref(),
+            )
+            .await?;
+
+        let mut invoice = InvoiceRequest::builder(request.email.clone(), price.offer.id)
+            .offer(&price.offer)
+            .currency(request.currency.clone());
+        if request.periodicity.is_some() {
+            invoice = invoice.periodicity(price.periodicity());
+        }
+        if let Some(method) = &request.payment_method {
+            invoice = invoice.payment_method(method.clone());
+        }
+        if let Some(language) = &request.buyer_language {
+            invoice = invoice.buyer_language(language.clone());
+        }
+        if let Some(utm) = &request.client_utm {
+            invoice = invoice.client_utm(utm.clone());
+        }
+        let invoice = invoice.build()?;
+
+        let response = self.client.create_invoice_v2(&invoice).await?;
+        Ok(CheckoutSession {
+            contract_id: response.id,
+            status: response.status,
+            payment_url: response.payment_url,
+            expected_amount: price.expected_amount(),
+            amount_total: response.amount_total,
+            price,
+        })
+    }
+
+    async fn load_catalog(
+        &self,
+        force: bool,
+    ) -> Result<Arc<Vec<ProductItemResponse>>, LavaTopError> {
+        // Блокировка удерживается на время загрузки, чтобы параллельные вызовы не
+        // загружали каталог повторно
+        let mut cached = self.catalog.lock().await;
+        let max_age = if force {
+            MIN_RELOAD_INTERVAL.min(self.ttl)
+        } else {
+            self.ttl
+        };
+        if let Some(catalog) = cached.as_ref()
+            && catalog.loaded_at.elapsed() < max_age
+        {
+            return Ok(catalog.products.clone());
+        }
+        let params = ListProductsParams {
+            show_all_subscription_periods: Some(true),
+            ..Default::default()
+        };
+        let products: Vec<_> = self
+            .client
+            .products_stream(params)
+            .try_filter_map(|item| async move {
+                Ok(match item.data {
+                    FeedData::Product(product) => Some(product),
+                    FeedData::Post(_) => None,
+                })
+            })
+            .try_collect()
+            .await?;
+        let products = Arc::new(products);
+        *cached = Some(CachedCatalog {
+            products: products.clone(),
+            loaded_at: Instant::now(),
+        });
+        Ok(products)
+    }
+}
+
+/// Причина, по которой цена не найдена.
+enum Lookup {
+    /// Продукта или оффера нет в каталоге; может помочь перезагрузка.
+    Missing(ValidationErrors),
+    /// Запрос неоднозначен или у оффера нет подходящей цены.
+    Invalid(ValidationErrors),
+}
+
+impl Lookup {
+    fn missing(field: &str, issue: FieldIssue) -> Self {
+        Lookup::Missing(single_error(field, issue))
+    }
+
+    fn invalid(field: &str, issue: FieldIssue) -> Self {
+        Lookup::Invalid(single_error(field, issue))
+    }
+
+    fn into_error(self) -> LavaTopError {
+        match self {
+            Lookup::Missing(errors) | Lookup::Invalid(errors) => LavaTopError::Validation(errors),
+        }
+    }
+}
+
+fn single_error(field: &str, issue: FieldIssue) -> ValidationErrors {
+    let mut errors = ValidationErrors::default();
+    errors.push(field, issue);
+    errors
+}
+
+fn same_name(name: Option<&str>, expected: &str) -> bool {
+    name.is_some_and(|name| name.trim().to_lowercase() == expected.trim().to_lowercase())
+}
+
+fn find_price(
+    catalog: &[ProductItemResponse],
+    product: &ProductRef,
+    offer_name: &str,
+    currency: &CurrencyDto,
+    periodicity: Option<&Periodicity>,
+) -> Result<ResolvedPrice, Lookup> {
+    let products: Vec<_> = catalog
+        .iter()
+        .filter(|item| match product {
+            ProductRef::Id(id) => item.id == *id,
+            ProductRef::Title(title) => same_name(item.title.as_deref(), title),
+        })
+        .collect();
+    let product = match products.as_slice() {
+        [] => {
+            return Err(Lookup::missing(
+                "product",
+                FieldIssue::ProductNotFound(product.clone()),
+            ));
+        }
+        [product] => *product,
+        _ => {
+            return Err(Lookup::invalid(
+                "product",
+                FieldIssue::AmbiguousProduct(products.iter().map(|p| p.id).collect()),
+            ));
+        }
+    };
+
+    let offers: Vec<_> = product
+        .offers
+        .iter()
+        .filter(|offer| same_name(offer.name.as_deref(), offer_name))
+        .collect();
+    let offer = match offers.as_slice() {
+        [] => {
+            return Err(Lookup::missing(
+                "offer",
+                FieldIssue::OfferNotFound {
+                    product_id: product.id,
+                    offer: offer_name.to_string(),
+                },
+            ));
+        }
+        [offer] => *offer,
+        _ => {
+            return Err(Lookup::invalid(
+                "offer",
+                FieldIssue::AmbiguousOffer {
+                    product_id: product.id,
+                    offer: offer_name.to_string(),
+                },
+            ));
+        }
+    };
+
+    let wanted = periodicity.cloned().unwrap_or(Periodicity::OneTime);
+    let price = offer
+        .prices
+        .iter()
+        .find(|price| price.currency == *currency && price.periodicity_or_one_time() == wanted);
+    match price {
+        Some(price) => Ok(ResolvedPrice {
+            product_id: product.id,
+            offer: offer.clone(),
+            price: price.clone(),
+        }),
+        None => {
+            let available = offer
+                .prices
+                .iter()
+                .map(|price| (price.currency.clone(), price.periodicity_or_one_time()))
+                .collect();
+            let field = if offer.prices.iter().any(|p| p.currency == *currency) {
+                "periodicity"
+            } else {
+                "currency"
+            };
+            Err(Lookup::invalid(
+                field,
+                FieldIssue::NoMatchingPrice {
+                    currency: currency.clone(),
+                    periodicity: wanted,
+                    available,
+                },
+            ))
+        }
+    }
+}
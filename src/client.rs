@@ -1,4 +1,4 @@
-use crate::error::{ApiErrorDetails, LavaTopError};
+use crate::error::LavaTopError;
 use crate::models::common::PagedResponseV2;
 use crate::models::donate::DonateResponse;
 use crate::models::feed::{FeedPageResponse, GetFeedParams};
@@ -14,51 +14,116 @@ use crate::models::report::{
     PartnerSalesPageResponse,
 };
 use crate::models::subscription::CancelSubscriptionParams;
+use crate::rate_limit::EndpointGroup;
+use crate::secret::ApiKey;
+use crate::transport::{BoxTransport, HttpResponse};
+use bytes::Bytes;
 use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderValue};
-use reqwest::{Client as ReqwestClient, Method, Response, StatusCode};
+use reqwest::{Method, StatusCode};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::time::Duration;
+use tower::ServiceExt;
+#[cfg(feature = "tracing")]
+use tracing::field::Empty;
 use url::Url;
 use uuid::Uuid;
 
-const API_KEY_HEADER: &str = "X-Api-Key";
+mod api;
+mod builder;
+mod pagination;
+mod wait;
+
+pub use api::LavaTopApi;
+#[cfg(feature = "mock")]
+pub use api::MockLavaTopApi;
+pub use builder::LavaTopClientBuilder;
+pub use wait::{WaitOutcome, WaitPolicy};
+
+pub(crate) const API_KEY_HEADER: &str = "X-Api-Key";
+pub(crate) const DEFAULT_BASE_URL: &str = "https://gate.lava.top/";
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Асинхронный клиент для взаимодействия с Lava Top API.
+///
+/// Клиент не запускает фоновых задач и работает на любом исполнителе, в том числе
+/// однопоточном. Исключение — транспорт по умолчанию на reqwest: ему нужен runtime tokio
+/// (достаточно `current_thread`). С другими исполнителями передайте свой транспорт через
+/// [`LavaTopClientBuilder::transport`] и отключите feature `tokio`, тогда таймауты, повторы
+/// и ограничитель частоты используют таймер `futures-timer`.
+///
+/// Однопоточный исполнитель `futures` без tokio (с `--no-default-features`): первая попытка
+/// получает 503, и повтор ждет на таймере `futures-timer`. Тот же сценарий с транспортом
+/// поверх `std::net` проверяет `tests/no_tokio.rs` (`cargo test --no-default-features`).
+///
+/// ```
+/// # use lava_top_rs::client::LavaTopClient;
+/// # use lava_top_rs::retry::RetryPolicy;
+/// # use lava_top_rs::transport::HttpRequest;
+/// # use std::sync::Arc;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// # use std::time::Duration;
+/// let attempts = Arc::new(AtomicUsize::new(0));
+/// let counter = attempts.clone();
+/// let transport = tower::service_fn(move |_request: HttpRequest| {
+///     let attempt = counter.fetch_add(1, Ordering::SeqCst);
+///     async move {
+///         let (status, body) = match attempt {
+///             0 => (503, ""),
+///             _ => (200, r#"{"url":"https://lava.top/donate/example"}"#),
+///         };
+///         http::Response::builder()
+///             .status(status)
+///             .body(bytes::Bytes::from(body))
+///             .map_err(std::io::Error::other)
+///     }
+/// });
+///
+/// let client = LavaTopClient::builder()
+///     .api_key("my-api-key")
+///     .transport(transport)
+///     .timeout(Duration::from_secs(5))
+///     .retry_policy(RetryPolicy::new().initial_backoff(Duration::from_millis(10)))
+///     .build()?;
+/// // Таймеры tokio требуют его runtime, поэтому без него пример запускается только без feature `tokio`
+/// # if cfg!(feature = "tokio") { return Ok(()); }
+/// let donate = futures::executor::block_on(client.get_donate_link())?;
+/// assert_eq!(donate.url.as_str(), "https://lava.top/donate/example");
+/// assert_eq!(attempts.load(Ordering::SeqCst), 2);
+/// # Ok::<(), lava_top_rs::error::LavaTopError>(())
+/// ```
 #[derive(Clone, Debug)]
 pub struct LavaTopClient {
-    client: ReqwestClient,
-    api_key: String,
+    api_key: ApiKey,
     base_url: Url,
+    default_headers: HeaderMap,
+    transport: BoxTransport,
 }
 
+/// Путь запроса, сохраненный в расширениях ответа для сообщений об ошибках разбора.
+#[derive(Debug, Clone)]
+struct RequestPath(String);
+
 impl LavaTopClient {
     /// Создает новый экземпляр клиента.
     ///
+    /// Для тонкой настройки HTTP клиента используйте [`LavaTopClient::builder`].
+    ///
     /// # Arguments
     ///
     /// * `api_key` - Ваш API ключ (X-Api-Key).
     /// * `base_url` - Опциональный базовый URL API. По умолчанию используется "https://gate.lava.top/".
     pub fn new(api_key: String, base_url: Option<Url>) -> Result<Self, LavaTopError> {
-        let base = base_url.unwrap_or_else(|| {
-            Url::parse("https://gate.lava.top/")
-                .expect("Неверный базовый URL по умолчанию. Это ошибка в библиотеке.")
-        });
-
-        // Проверяем, что API ключ не пустой
-        if api_key.trim().is_empty() {
-            return Err(LavaTopError::MissingParameter(
-                "api_key не может быть пустым".to_string(),
-            ));
+        let mut builder = Self::builder().api_key(api_key);
+        if let Some(url) = base_url {
+            builder = builder.base_url(url);
         }
+        builder.build()
+    }
 
-        Ok(LavaTopClient {
-            client: ReqwestClient::builder()
-                .timeout(Duration::from_secs(30))
-                .build()?, // Преобразуем ошибку reqwest в LavaTopError
-            api_key,
-            base_url: base,
-        })
+    /// Создает построитель клиента с настраиваемыми таймаутами, прокси, TLS и заголовками.
+    pub fn builder() -> LavaTopClientBuilder {
+        LavaTopClientBuilder::new()
     }
 
     /// Устанавливает новый базовый URL для клиента.
@@ -80,78 +145,115 @@ impl LavaTopClient {
         endpoint: &str,
         query_params: Option<&P>,
         json_body: Option<&T>,
-    ) -> Result<Response, LavaTopError> {
+    ) -> Result<HttpResponse, LavaTopError> {
         let url = self.build_url(endpoint)?;
-        let mut headers = HeaderMap::new();
-        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
-        headers.insert(API_KEY_HEADER, HeaderValue::from_str(&self.api_key)?);
-
-        let mut request_builder = self.client.request(method, url.clone()); // Клонируем URL для дебага
+        let group = EndpointGroup::for_endpoint(endpoint);
+        self.send_request_to_url(method, url, group, query_params, json_body)
+            .await
+    }
 
+    /// Внутренний метод для отправки запросов на полный URL (например, `nextPage`).
+    ///
+    /// `group` сохраняется в расширениях запроса для ограничителя частоты.
+    async fn send_request_to_url<T: Serialize, P: Serialize>(
+        &self,
+        method: Method,
+        mut url: Url,
+        group: EndpointGroup,
+        query_params: Option<&P>,
+        json_body: Option<&T>,
+    ) -> Result<HttpResponse, LavaTopError> {
         if let Some(params) = query_params {
-            request_builder = request_builder.query(params);
+            let query = serde_urlencoded::to_string(params)
+                .map_err(|e| LavaTopError::InvalidQueryParam(e.to_string()))?;
+            let query = match url.query() {
+                Some(existing) if !existing.is_empty() => format!("{existing}&{query}"),
+                _ => query,
+            };
+            if !query.is_empty() {
+                url.set_query(Some(&query));
+            }
         }
 
+        let path = RequestPath(url.path().to_string());
+        let mut request = http::Request::builder()
+            .method(method)
+            .uri(url.as_str())
+            .body(Bytes::new())
+            .map_err(|e| LavaTopError::Transport(e.into()))?;
+        request.extensions_mut().insert(group);
+        let headers = request.headers_mut();
+        headers.extend(self.default_headers.clone());
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        headers.insert(API_KEY_HEADER, self.api_key.header_value().await?);
+
         if let Some(body) = json_body {
             // Добавляем Content-Type только если есть тело
-            request_builder = request_builder.header(CONTENT_TYPE, "application/json");
-            request_builder = request_builder.json(body);
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            *request.body_mut() = serde_json::to_vec(body)?.into();
         }
 
-        // Добавляем заголовки ПОСЛЕ установки тела/параметров, чтобы они не были перезаписаны
-        request_builder = request_builder.headers(headers);
-
-        // Debug: Вывод URL и тела
-        // if let Ok(req_info) = request_builder.try_clone().map(|rb| rb.build()).and_then(|r| r) {
-        //     println!("Request URL: {}", req_info.url());
-        //     if let Some(body_bytes) = req_info.body().and_then(|b| b.as_bytes()) {
-        //          if let Ok(body_str) = std::str::from_utf8(body_bytes) {
-        //              println!("Request Body: {}", body_str);
-        //          }
-        //     }
-        // }
-
-        let response = request_builder.send().await?;
-
-        // Debug: Вывод статуса ответа
-        // println!("Response Status: {}", response.status());
-
-        Ok(response)
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            method = %request.method(),
+            url = %crate::redact::url(&url),
+            headers = ?crate::redact::headers(request.headers()),
+            body = %crate::redact::body(request.body()),
+            "запрос к Lava Top API"
+        );
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+
+        let result = self
+            .transport
+            .clone()
+            .oneshot(request)
+            .await
+            .map_err(LavaTopError::from_transport)
+            .map(|mut response| {
+                response.extensions_mut().insert(path);
+                response
+            });
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::Span::current();
+            span.record("latency_ms", started.elapsed().as_millis() as u64);
+            match &result {
+                Ok(response) => {
+                    span.record("status", response.status().as_u16());
+                    tracing::debug!(
+                        status = response.status().as_u16(),
+                        body = %crate::redact::body(response.body()),
+                        "ответ Lava Top API"
+                    );
+                }
+                Err(error) => tracing::debug!(%error, "запрос к Lava Top API не выполнен"),
+            }
+        }
+        result
     }
 
     /// Внутренний метод для обработки ответа и десериализации JSON.
     async fn process_response<R: DeserializeOwned>(
         &self,
-        response: Response,
+        response: HttpResponse,
     ) -> Result<R, LavaTopError> {
         let status = response.status();
         if status.is_success() {
             // Успешный ответ (2xx)
-            // Пытаемся десериализовать тело
-            match response.json::<R>().await {
-                Ok(data) => Ok(data),
-                Err(e) => {
-                    // Если десериализация не удалась даже при успешном статусе
-                    Err(LavaTopError::Reqwest(e))
-                }
-            }
+            // Пытаемся десериализовать тело, запоминая путь к полю на случай ошибки
+            let deserializer = &mut serde_json::Deserializer::from_slice(response.body());
+            serde_path_to_error::deserialize(deserializer).map_err(|e| {
+                let endpoint = response
+                    .extensions()
+                    .get::<RequestPath>()
+                    .map_or("", |path| path.0.as_str());
+                LavaTopError::decode(endpoint, status, response.body(), e)
+            })
         } else {
             // Ошибка API (не 2xx)
-            let raw_body = response.text().await.ok();
-            // Debug: Вывод тела ошибки
-            // if let Some(ref body) = raw_body {
-            //     println!("Response Body (Error): {}", body);
-            // }
-            // Пытаемся парсить как стандартную структуру ошибки API
-            let details: Option<ApiErrorDetails> = raw_body
-                .as_deref()
-                .and_then(|body| serde_json::from_str(body).ok());
-
-            Err(LavaTopError::ApiError {
-                status,
-                details,
-                raw_body,
-            })
+            Err(LavaTopError::from_response(&response))
         }
     }
 
@@ -160,6 +262,14 @@ impl LavaTopClient {
     // == Feed (Deprecated) ==
     #[deprecated(note = "Эндпоинт /api/v1/feed устарел. Используйте list_products_v2.")]
     /// Получение ленты продуктов и постов (устаревший метод).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "/api/v1/feed", status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn get_feed(
         &self,
         params: Option<&GetFeedParams>,
@@ -174,6 +284,14 @@ impl LavaTopClient {
 
     #[deprecated(note = "Эндпоинт POST /api/v1/invoice устарел. Используйте create_invoice_v2.")]
     /// Создание контракта на покупку контента (устаревший метод v1).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "POST", endpoint = "/api/v1/invoice", offer_id = %request.offer_id, contract_id = Empty, status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn create_invoice_v1(
         &self,
         request: &InvoiceRequestDto,
@@ -181,10 +299,21 @@ impl LavaTopClient {
         let response = self
             .send_request::<_, ()>(Method::POST, "/api/v1/invoice", None, Some(request))
             .await?;
-        self.process_response(response).await
+        let invoice: InvoicePaymentParamsResponse = self.process_response(response).await?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("contract_id", tracing::field::display(invoice.id));
+        Ok(invoice)
     }
 
     /// Создание контракта на покупку контента (v2).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "POST", endpoint = "/api/v2/invoice", offer_id = %request.offer_id, contract_id = Empty, status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn create_invoice_v2(
         &self,
         request: &InvoiceRequestDto,
@@ -192,10 +321,21 @@ impl LavaTopClient {
         let response = self
             .send_request::<_, ()>(Method::POST, "/api/v2/invoice", None, Some(request))
             .await?;
-        self.process_response(response).await
+        let invoice: InvoicePaymentParamsResponse = self.process_response(response).await?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("contract_id", tracing::field::display(invoice.id));
+        Ok(invoice)
     }
 
     /// Получение страницы контрактов API-ключа.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "/api/v1/invoices", status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn list_invoices(
         &self,
         params: Option<&ListInvoicesParams>,
@@ -207,6 +347,14 @@ impl LavaTopClient {
     }
 
     /// Получение контракта по идентификатору.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "/api/v1/invoices/{id}", contract_id = %invoice_id, status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn get_invoice_by_id(
         &self,
         invoice_id: &Uuid,
@@ -221,6 +369,14 @@ impl LavaTopClient {
     // == Sales (Reports) ==
 
     /// Получение списка продаж партнёра (общий).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "/api/v1/sales/", status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn list_partner_sales(
         &self,
         params: Option<&ListPartnerSalesParams>,
@@ -232,6 +388,14 @@ impl LavaTopClient {
     }
 
     /// Получение списка продаж партнёра по конкретному продукту.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "/api/v1/sales/{productId}", product_id = %product_id, status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn list_partner_product_sales(
         &self,
         product_id: Uuid,
@@ -250,6 +414,14 @@ impl LavaTopClient {
     ///
     /// # Arguments
     /// * `params` - Параметры отмены, содержащие `contract_id` и `email`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "DELETE", endpoint = "/api/v1/subscriptions", contract_id = %params.contract_id, status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn cancel_subscription(
         &self,
         params: &CancelSubscriptionParams,
@@ -263,28 +435,26 @@ impl LavaTopClient {
             Ok(())
         } else if status.is_success() {
             // Неожиданный успешный статус
-            eprintln!(
-                "Warning: cancel_subscription received unexpected success status: {}",
-                status
-            );
+            #[cfg(feature = "tracing")]
+            tracing::warn!(%status, "cancel_subscription: неожиданный успешный статус");
             Ok(())
         } else {
             // Обрабатываем ошибку как обычно
-            let raw_body = response.text().await.ok();
-            let details: Option<ApiErrorDetails> = raw_body
-                .as_deref()
-                .and_then(|body| serde_json::from_str(body).ok());
-            Err(LavaTopError::ApiError {
-                status,
-                details,
-                raw_body,
-            })
+            Err(LavaTopError::from_response(&response))
         }
     }
 
     // == Products ==
 
     /// Получение списка продуктов (v2).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "/api/v2/products", status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn list_products_v2(
         &self,
         params: Option<&ListProductsParams>,
@@ -296,6 +466,14 @@ impl LavaTopClient {
     }
 
     /// Обновление продукта.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "PATCH", endpoint = "/api/v2/products/{productId}", product_id = %product_id, status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn update_product(
         &self,
         product_id: Uuid,
@@ -311,6 +489,14 @@ impl LavaTopClient {
     // == Donate ==
 
     /// Получение ссылки на донат аккаунта.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "/api/v1/donate", status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
     pub async fn get_donate_link(&self) -> Result<DonateResponse, LavaTopError> {
         let response = self
             .send_request::<(), ()>(Method::GET, "/api/v1/donate", None, None)
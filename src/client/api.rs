@@ -0,0 +1,172 @@
+use crate::client::LavaTopClient;
+use crate::error::LavaTopError;
+use crate::models::common::PagedResponseV2;
+use crate::models::donate::DonateResponse;
+use crate::models::feed::{FeedPageResponse, GetFeedParams};
+use crate::models::invoice::{
+    InvoicePageResponse, InvoicePaymentParamsResponse, InvoiceRequestDto, InvoiceResponseV2,
+    ListInvoicesParams,
+};
+use crate::models::product::{
+    FeedItemCombined, ListProductsParams, ProductItemResponse, ProductUpdateRequest,
+};
+use crate::models::report::{
+    ListPartnerProductSalesParams, ListPartnerSalesParams, PartnerProductSalesPageResponse,
+    PartnerSalesPageResponse,
+};
+use crate::models::subscription::CancelSubscriptionParams;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+/// Все эндпоинты Lava Top API в виде трейта.
+///
+/// Позволяет подменять клиент в тестах и оборачивать его (кэширование, аудит) без
+/// пересылки каждого метода вручную. Трейт объектно-безопасен, поэтому его можно хранить
+/// как `Arc<dyn LavaTopApi>`. С feature `mock` доступна сгенерированная реализация
+/// [`MockLavaTopApi`](crate::client::MockLavaTopApi).
+#[cfg_attr(feature = "mock", mockall::automock)]
+#[async_trait]
+pub trait LavaTopApi: Send + Sync {
+    /// См. [`LavaTopClient::get_feed`]. Эндпоинт устарел, используйте `list_products_v2`.
+    async fn get_feed<'a>(
+        &self,
+        params: Option<&'a GetFeedParams>,
+    ) -> Result<FeedPageResponse, LavaTopError>;
+
+    /// См. [`LavaTopClient::create_invoice_v1`]. Эндпоинт устарел, используйте `create_invoice_v2`.
+    async fn create_invoice_v1(
+        &self,
+        request: &InvoiceRequestDto,
+    ) -> Result<InvoicePaymentParamsResponse, LavaTopError>;
+
+    /// См. [`LavaTopClient::create_invoice_v2`].
+    async fn create_invoice_v2(
+        &self,
+        request: &InvoiceRequestDto,
+    ) -> Result<InvoicePaymentParamsResponse, LavaTopError>;
+
+    /// См. [`LavaTopClient::list_invoices`].
+    async fn list_invoices<'a>(
+        &self,
+        params: Option<&'a ListInvoicesParams>,
+    ) -> Result<InvoicePageResponse, LavaTopError>;
+
+    /// См. [`LavaTopClient::get_invoice_by_id`].
+    async fn get_invoice_by_id(&self, invoice_id: &Uuid)
+    -> Result<InvoiceResponseV2, LavaTopError>;
+
+    /// См. [`LavaTopClient::list_partner_sales`].
+    async fn list_partner_sales<'a>(
+        &self,
+        params: Option<&'a ListPartnerSalesParams>,
+    ) -> Result<PartnerSalesPageResponse, LavaTopError>;
+
+    /// См. [`LavaTopClient::list_partner_product_sales`].
+    async fn list_partner_product_sales<'a>(
+        &self,
+        product_id: Uuid,
+        params: Option<&'a ListPartnerProductSalesParams>,
+    ) -> Result<PartnerProductSalesPageResponse, LavaTopError>;
+
+    /// См. [`LavaTopClient::cancel_subscription`].
+    async fn cancel_subscription(
+        &self,
+        params: &CancelSubscriptionParams,
+    ) -> Result<(), LavaTopError>;
+
+    /// См. [`LavaTopClient::list_products_v2`].
+    async fn list_products_v2<'a>(
+        &self,
+        params: Option<&'a ListProductsParams>,
+    ) -> Result<PagedResponseV2<FeedItemCombined>, LavaTopError>;
+
+    /// См. [`LavaTopClient::update_product`].
+    async fn update_product(
+        &self,
+        product_id: Uuid,
+        request: &ProductUpdateRequest,
+    ) -> Result<ProductItemResponse, LavaTopError>;
+
+    /// См. [`LavaTopClient::get_donate_link`].
+    async fn get_donate_link(&self) -> Result<DonateResponse, LavaTopError>;
+}
+
+#[async_trait]
+#[allow(deprecated)]
+impl LavaTopApi for LavaTopClient {
+    async fn get_feed<'a>(
+        &self,
+        params: Option<&'a GetFeedParams>,
+    ) -> Result<FeedPageResponse, LavaTopError> {
+        LavaTopClient::get_feed(self, params).await
+    }
+
+    async fn create_invoice_v1(
+        &self,
+        request: &InvoiceRequestDto,
+    ) -> Result<InvoicePaymentParamsResponse, LavaTopError> {
+        LavaTopClient::create_invoice_v1(self, request).await
+    }
+
+    async fn create_invoice_v2(
+        &self,
+        request: &InvoiceRequestDto,
+    ) -> Result<InvoicePaymentParamsResponse, LavaTopError> {
+        LavaTopClient::create_invoice_v2(self, request).await
+    }
+
+    async fn list_invoices<'a>(
+        &self,
+        params: Option<&'a ListInvoicesParams>,
+    ) -> Result<InvoicePageResponse, LavaTopError> {
+        LavaTopClient::list_invoices(self, params).await
+    }
+
+    async fn get_invoice_by_id(
+        &self,
+        invoice_id: &Uuid,
+    ) -> Result<InvoiceResponseV2, LavaTopError> {
+        LavaTopClient::get_invoice_by_id(self, invoice_id).await
+    }
+
+    async fn list_partner_sales<'a>(
+        &self,
+        params: Option<&'a ListPartnerSalesParams>,
+    ) -> Result<PartnerSalesPageResponse, LavaTopError> {
+        LavaTopClient::list_partner_sales(self, params).await
+    }
+
+    async fn list_partner_product_sales<'a>(
+        &self,
+        product_id: Uuid,
+        params: Option<&'a ListPartnerProductSalesParams>,
+    ) -> Result<PartnerProductSalesPageResponse, LavaTopError> {
+        LavaTopClient::list_partner_product_sales(self, product_id, params).await
+    }
+
+    async fn cancel_subscription(
+        &self,
+        params: &CancelSubscriptionParams,
+    ) -> Result<(), LavaTopError> {
+        LavaTopClient::cancel_subscription(self, params).await
+    }
+
+    async fn list_products_v2<'a>(
+        &self,
+        params: Option<&'a ListProductsParams>,
+    ) -> Result<PagedResponseV2<FeedItemCombined>, LavaTopError> {
+        LavaTopClient::list_products_v2(self, params).await
+    }
+
+    async fn update_product(
+        &self,
+        product_id: Uuid,
+        request: &ProductUpdateRequest,
+    ) -> Result<ProductItemResponse, LavaTopError> {
+        LavaTopClient::update_product(self, product_id, request).await
+    }
+
+    async fn get_donate_link(&self) -> Result<DonateResponse, LavaTopError> {
+        LavaTopClient::get_donate_link(self).await
+    }
+}
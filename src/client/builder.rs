@@ -0,0 +1,461 @@
+use crate::client::{API_KEY_HEADER, DEFAULT_BASE_URL, DEFAULT_TIMEOUT, LavaTopClient};
+use crate::error::{BoxError, ConfigError, LavaTopError};
+use crate::rate_limit::{RateLimitLayer, RateLimiter};
+use crate::retry::{RetryLayer, RetryPolicy};
+use crate::secret::{self, ApiKey, ApiKeyProvider, SecretString};
+use crate::transport::{BoxTransport, HttpRequest, HttpResponse, ReqwestTransport, TimeoutLayer};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use reqwest::tls::Version as TlsVersion;
+use reqwest::{Certificate, Client as ReqwestClient, Proxy};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::{Layer, Service, ServiceExt};
+use url::Url;
+
+/// Пользовательский слой, приведенный к преобразованию [`BoxTransport`].
+struct TransportLayer(Box<dyn FnOnce(BoxTransport) -> BoxTransport + Send + Sync>);
+
+impl fmt::Debug for TransportLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TransportLayer")
+    }
+}
+
+/// Откуда построитель берет API ключ.
+enum ApiKeySource {
+    Value(SecretString),
+    Env(String),
+    File(PathBuf),
+    Provider(Arc<dyn ApiKeyProvider>),
+}
+
+impl fmt::Debug for ApiKeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKeySource::Value(secret) => f.debug_tuple("Value").field(secret).finish(),
+            ApiKeySource::Env(name) => f.debug_tuple("Env").field(name).finish(),
+            ApiKeySource::File(path) => f.debug_tuple("File").field(path).finish(),
+            ApiKeySource::Provider(_) => f.write_str("Provider(..)"),
+        }
+    }
+}
+
+impl ApiKeySource {
+    fn resolve(self) -> Result<ApiKey, LavaTopError> {
+        let secret = match self {
+            ApiKeySource::Provider(provider) => return Ok(ApiKey::Provider(provider)),
+            ApiKeySource::Value(secret) => secret,
+            ApiKeySource::Env(name) => std::env::var(&name)
+                .map(SecretString::from)
+                .map_err(|_| ConfigError::ApiKeyEnvMissing(name))?,
+            ApiKeySource::File(path) => {
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|source| ConfigError::ApiKeyFile { path, source })?;
+                let content = SecretString::from(content);
+                SecretString::from(content.expose_secret().trim())
+            }
+        };
+        // Проверяем, что API ключ не пустой
+        if secret.is_blank() {
+            return Err(LavaTopError::MissingParameter("api_key".to_string()));
+        }
+        // Проверяем значение заранее, чтобы ошибка возникла в build, а не при первом запросе
+        secret::header_value(&secret)?;
+        Ok(ApiKey::Static(secret))
+    }
+}
+
+/// Приводит сервис с произвольным типом ошибки к [`BoxTransport`].
+fn boxed<S>(service: S) -> BoxTransport
+where
+    S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    BoxTransport::new(service.map_err(Into::into))
+}
+
+/// Построитель для [`LavaTopClient`].
+///
+/// Все параметры проверяются в [`LavaTopClientBuilder::build`], до первого запроса к API.
+///
+/// ```no_run
+/// # use lava_top_rs::client::LavaTopClient;
+/// # use std::time::Duration;
+/// # fn main() -> Result<(), lava_top_rs::error::LavaTopError> {
+/// let client = LavaTopClient::builder()
+///     .api_key("my-api-key")
+///     .connect_timeout(Duration::from_secs(5))
+///     .timeout(Duration::from_secs(20))
+///     .user_agent("billing-service/1.0")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct LavaTopClientBuilder {
+    api_key: Option<ApiKeySource>,
+    base_url: Option<Url>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    default_headers: HeaderMap,
+    proxies: Vec<Proxy>,
+    no_proxy: bool,
+    root_certificates: Vec<Certificate>,
+    tls_built_in_root_certs: Option<bool>,
+    min_tls_version: Option<TlsVersion>,
+    danger_accept_invalid_certs: bool,
+    https_only: bool,
+    http_client: Option<ReqwestClient>,
+    transport: Option<BoxTransport>,
+    layers: Vec<TransportLayer>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl LavaTopClientBuilder {
+    /// Создает построитель с параметрами по умолчанию.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// API ключ (X-Api-Key). Обязателен один из способов задать ключ: этот метод,
+    /// [`Self::api_key_from_env`], [`Self::api_key_from_file`] или [`Self::api_key_provider`].
+    pub fn api_key(mut self, api_key: impl Into<SecretString>) -> Self {
+        self.api_key = Some(ApiKeySource::Value(api_key.into()));
+        self
+    }
+
+    /// API ключ из переменной окружения, прочитанной в [`Self::build`].
+    pub fn api_key_from_env(mut self, name: impl Into<String>) -> Self {
+        self.api_key = Some(ApiKeySource::Env(name.into()));
+        self
+    }
+
+    /// API ключ из файла (например, Docker или Kubernetes secret), прочитанного в [`Self::build`].
+    ///
+    /// Пробельные символы в начале и конце файла отбрасываются.
+    pub fn api_key_from_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.api_key = Some(ApiKeySource::File(path.into()));
+        self
+    }
+
+    /// Провайдер API ключа, который вызывается перед каждым вызовом API.
+    ///
+    /// Подходит для ключей из хранилища секретов с ротацией. Ошибка провайдера
+    /// возвращается как [`LavaTopError::ApiKey`].
+    ///
+    /// ```no_run
+    /// # use lava_top_rs::client::LavaTopClient;
+    /// # use lava_top_rs::error::BoxError;
+    /// # use lava_top_rs::secret::SecretString;
+    /// # async fn fetch_from_vault() -> Result<String, BoxError> { unimplemented!() }
+    /// # fn main() -> Result<(), lava_top_rs::error::LavaTopError> {
+    /// let client = LavaTopClient::builder()
+    ///     .api_key_provider(|| async { fetch_from_vault().await.map(SecretString::from) })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn api_key_provider(mut self, provider: impl ApiKeyProvider + 'static) -> Self {
+        self.api_key = Some(ApiKeySource::Provider(Arc::new(provider)));
+        self
+    }
+
+    /// Базовый URL API. По умолчанию используется "https://gate.lava.top/".
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Общий таймаут запроса (от отправки до получения всего тела ответа).
+    ///
+    /// По умолчанию 30 секунд, если клиент reqwest не передан через [`Self::http_client`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Таймаут установки соединения.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Таймаут чтения ответа (между поступлениями данных из сокета).
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Значение заголовка `User-Agent` для всех запросов.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Добавляет заголовок, отправляемый с каждым запросом.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    /// Заменяет набор заголовков, отправляемых с каждым запросом.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Добавляет прокси для исходящих запросов.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Отключает использование прокси, в том числе системных.
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Добавляет доверенный корневой сертификат.
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Включает или отключает встроенные корневые сертификаты.
+    pub fn tls_built_in_root_certs(mut self, enabled: bool) -> Self {
+        self.tls_built_in_root_certs = Some(enabled);
+        self
+    }
+
+    /// Минимальная допустимая версия TLS.
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Self {
+        self.min_tls_version = Some(version);
+        self
+    }
+
+    /// Отключает проверку сертификатов сервера.
+    ///
+    /// # Warning
+    ///
+    /// Используйте только для отладки: любой сертификат будет считаться валидным.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.danger_accept_invalid_certs = accept;
+        self
+    }
+
+    /// Запрещает запросы по незащищенному HTTP.
+    pub fn https_only(mut self, enabled: bool) -> Self {
+        self.https_only = enabled;
+        self
+    }
+
+    /// Использовать готовый клиент reqwest вместо создания нового.
+    ///
+    /// Параметры транспорта (таймауты соединения и чтения, прокси, TLS) в этом случае
+    /// задаются на переданном клиенте, и их повторная установка в построителе приводит к ошибке.
+    /// Общий таймаут, `User-Agent` и заголовки по умолчанию применяются к каждому запросу.
+    pub fn http_client(mut self, client: ReqwestClient) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Заменяет транспорт reqwest собственным `tower::Service` (например, уже существующим стеком).
+    ///
+    /// Слои повторов и таймаута, а также слои из [`Self::layer`] оборачивают переданный сервис.
+    /// Параметры соединения, прокси и TLS, как и [`Self::http_client`], в этом случае задать нельзя.
+    pub fn transport<S>(mut self, service: S) -> Self
+    where
+        S: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        self.transport = Some(boxed(service));
+        self
+    }
+
+    /// Добавляет слой tower (логирование, метрики, подпись запросов и т.п.).
+    ///
+    /// Как и в `tower::ServiceBuilder`, слой, добавленный первым, оказывается снаружи.
+    /// Все пользовательские слои находятся внутри слоев повторов, ограничителя частоты и таймаута,
+    /// поэтому вызываются на каждую попытку запроса.
+    ///
+    /// ```no_run
+    /// # use lava_top_rs::client::LavaTopClient;
+    /// # use lava_top_rs::transport::HttpRequest;
+    /// # use tower::util::MapRequestLayer;
+    /// # fn main() -> Result<(), lava_top_rs::error::LavaTopError> {
+    /// let client = LavaTopClient::builder()
+    ///     .api_key("my-api-key")
+    ///     .layer(MapRequestLayer::new(|mut request: HttpRequest| {
+    ///         request
+    ///             .headers_mut()
+    ///             .insert("x-request-source", "billing".parse().unwrap());
+    ///         request
+    ///     }))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<BoxTransport> + Send + Sync + 'static,
+        L::Service: Service<HttpRequest, Response = HttpResponse> + Clone + Send + Sync + 'static,
+        <L::Service as Service<HttpRequest>>::Error: Into<BoxError>,
+        <L::Service as Service<HttpRequest>>::Future: Send + 'static,
+    {
+        self.layers.push(TransportLayer(Box::new(move |inner| {
+            boxed(layer.layer(inner))
+        })));
+        self
+    }
+
+    /// Политика повторных попыток для временных ошибок.
+    ///
+    /// По умолчанию используется [`RetryPolicy::default`]; чтобы отключить повторы,
+    /// передайте [`RetryPolicy::disabled`].
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Ограничитель частоты запросов.
+    ///
+    /// По умолчанию ограничителя нет. Передайте один ограничитель нескольким клиентам,
+    /// чтобы они делили квоту.
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Проверяет параметры и создает клиент.
+    pub fn build(self) -> Result<LavaTopClient, LavaTopError> {
+        let api_key = self
+            .api_key
+            .unwrap_or(ApiKeySource::Value(SecretString::default()))
+            .resolve()?;
+
+        let base_url = match self.base_url {
+            Some(url) => normalize_base_url(url)?,
+            None => Url::parse(DEFAULT_BASE_URL)
+                .expect("Неверный базовый URL по умолчанию. Это ошибка в библиотеке."),
+        };
+        if self.https_only && base_url.scheme() != "https" {
+            return Err(ConfigError::HttpsOnly {
+                scheme: base_url.scheme().to_string(),
+            }
+            .into());
+        }
+
+        if self.default_headers.contains_key(API_KEY_HEADER) {
+            return Err(ConfigError::ApiKeyInDefaultHeaders.into());
+        }
+        let mut default_headers = self.default_headers;
+        if let Some(user_agent) = self.user_agent {
+            default_headers.insert(USER_AGENT, HeaderValue::from_str(&user_agent)?);
+        }
+
+        if self.timeout.is_some_and(|t| t.is_zero()) {
+            return Err(ConfigError::ZeroTimeout.into());
+        }
+
+        let transport_configured = self.connect_timeout.is_some()
+            || self.read_timeout.is_some()
+            || !self.proxies.is_empty()
+            || self.no_proxy
+            || !self.root_certificates.is_empty()
+            || self.tls_built_in_root_certs.is_some()
+            || self.min_tls_version.is_some()
+            || self.danger_accept_invalid_certs
+            || self.https_only;
+
+        let (mut transport, timeout) = match (self.transport, self.http_client) {
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::TransportAndHttpClient.into());
+            }
+            (Some(_), None) | (None, Some(_)) if transport_configured => {
+                return Err(ConfigError::ConnectionOptionsWithCustomClient.into());
+            }
+            (Some(transport), None) => (transport, self.timeout),
+            (None, Some(client)) => (boxed(ReqwestTransport::new(client)), self.timeout),
+            (None, None) => {
+                let mut builder = ReqwestClient::builder();
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.read_timeout {
+                    builder = builder.read_timeout(timeout);
+                }
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+                if self.no_proxy {
+                    builder = builder.no_proxy();
+                }
+                for certificate in self.root_certificates {
+                    builder = builder.add_root_certificate(certificate);
+                }
+                if let Some(enabled) = self.tls_built_in_root_certs {
+                    builder = builder.tls_built_in_root_certs(enabled);
+                }
+                if let Some(version) = self.min_tls_version {
+                    builder = builder.min_tls_version(version);
+                }
+                builder = builder
+                    .danger_accept_invalid_certs(self.danger_accept_invalid_certs)
+                    .https_only(self.https_only);
+                (
+                    boxed(ReqwestTransport::new(builder.build()?)), // Преобразуем ошибку reqwest в LavaTopError
+                    Some(self.timeout.unwrap_or(DEFAULT_TIMEOUT)),
+                )
+            }
+        };
+
+        // Стек снаружи внутрь: повторы -> ограничитель частоты (если задан) -> таймаут попытки ->
+        // слои пользователя -> транспорт. Ожидание в очереди не входит в таймаут.
+        for layer in self.layers.into_iter().rev() {
+            transport = (layer.0)(transport);
+        }
+        if let Some(timeout) = timeout {
+            transport = boxed(TimeoutLayer::new(timeout).layer(transport));
+        }
+        if let Some(rate_limiter) = self.rate_limiter {
+            transport = boxed(RateLimitLayer::new(rate_limiter).layer(transport));
+        }
+        transport = boxed(RetryLayer::new(self.retry_policy.unwrap_or_default()).layer(transport));
+
+        Ok(LavaTopClient {
+            api_key,
+            base_url,
+            default_headers,
+            transport,
+        })
+    }
+
+    /// Проверяет параметры и создает синхронный клиент [`crate::blocking::LavaTopClient`].
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<crate::blocking::LavaTopClient, LavaTopError> {
+        crate::blocking::LavaTopClient::from_async(self.build()?)
+    }
+}
+
+/// Проверяет базовый URL и добавляет завершающий `/`, чтобы `Url::join` не отбрасывал последний сегмент пути.
+pub(crate) fn normalize_base_url(mut url: Url) -> Result<Url, LavaTopError> {
+    if !matches!(url.scheme(), "http" | "https") || url.cannot_be_a_base() {
+        return Err(ConfigError::BaseUrlNotHttp(url).into());
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(ConfigError::BaseUrlHasQuery(url).into());
+    }
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    Ok(url)
+}
@@ -0,0 +1,290 @@
+use crate::client::LavaTopClient;
+use crate::error::LavaTopError;
+use crate::models::common::PagedResponseV2;
+use crate::models::invoice::{InvoiceResponseV2, ListInvoicesParams};
+use crate::models::product::{FeedItemCombined, ListProductsParams};
+use crate::models::report::{
+    ListPartnerProductSalesParams, ListPartnerSalesParams, PartnerProductDto, PartnerSaleDetailsDto,
+};
+use crate::rate_limit::EndpointGroup;
+use futures::stream::{self, Stream, TryStreamExt};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+#[cfg(feature = "tracing")]
+use tracing::field::Empty;
+use url::Url;
+use uuid::Uuid;
+
+/// Страница эндпоинтов с пагинацией по номеру (`page`/`size`).
+struct NumberedPage<T> {
+    items: Vec<T>,
+    page: i64,
+    size: i64,
+    total: i64,
+}
+
+/// Состояние обхода для эндпоинтов с курсором `nextPage`.
+enum CursorState {
+    First(ListProductsParams),
+    Next(Url),
+    Done,
+}
+
+/// Обходит все страницы, начиная с `start_page`, и отдает элементы по одному.
+///
+/// Обход завершается на пустой или неполной странице, а при старте с первой страницы —
+/// также после получения `total` элементов.
+fn numbered_stream<T, F, Fut>(
+    start_page: Option<i64>,
+    mut fetch: F,
+) -> impl Stream<Item = Result<T, LavaTopError>>
+where
+    F: FnMut(Option<i64>) -> Fut,
+    Fut: Future<Output = Result<NumberedPage<T>, LavaTopError>>,
+{
+    stream::try_unfold(Some((start_page, 0_i64)), move |state| {
+        let next = state.map(|(page, fetched)| (fetch(page), fetched));
+        async move {
+            let Some((request, fetched)) = next else {
+                return Ok::<_, LavaTopError>(None);
+            };
+            let page = request.await?;
+            let count = page.items.len() as i64;
+            let fetched = fetched + count;
+            let has_more =
+                count > 0 && count >= page.size && (start_page.is_some() || fetched < page.total);
+            let next_state = has_more.then_some((Some(page.page + 1), fetched));
+            Ok(Some((page.items, next_state)))
+        }
+    })
+    .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+    .try_flatten()
+}
+
+impl LavaTopClient {
+    /// Поток всех контрактов API-ключа, подходящих под `params`.
+    ///
+    /// Страницы запрашиваются по мере чтения потока; `params.page` задает первую страницу.
+    pub fn invoices_stream(
+        &self,
+        params: ListInvoicesParams,
+    ) -> impl Stream<Item = Result<InvoiceResponseV2, LavaTopError>> + Send + use<> {
+        let client = self.clone();
+        numbered_stream(params.page, move |page| {
+            let client = client.clone();
+            let params = ListInvoicesParams {
+                page,
+                ..params.clone()
+            };
+            async move {
+                let response = client.list_invoices(Some(&params)).await?;
+                Ok(NumberedPage {
+                    items: response.items,
+                    page: response.page,
+                    size: response.size,
+                    total: response.total,
+                })
+            }
+        })
+    }
+
+    /// Поток всех продаж партнёра (по продуктам).
+    pub fn partner_sales_stream(
+        &self,
+        params: ListPartnerSalesParams,
+    ) -> impl Stream<Item = Result<PartnerProductDto, LavaTopError>> + Send + use<> {
+        let client = self.clone();
+        numbered_stream(params.page, move |page| {
+            let client = client.clone();
+            let params = ListPartnerSalesParams {
+                page,
+                ..params.clone()
+            };
+            async move {
+                let response = client.list_partner_sales(Some(&params)).await?;
+                Ok(NumberedPage {
+                    items: response.items,
+                    page: response.page,
+                    size: response.size,
+                    total: response.total,
+                })
+            }
+        })
+    }
+
+    /// Поток всех продаж партнёра по конкретному продукту.
+    pub fn partner_product_sales_stream(
+        &self,
+        product_id: Uuid,
+        params: ListPartnerProductSalesParams,
+    ) -> impl Stream<Item = Result<PartnerSaleDetailsDto, LavaTopError>> + Send + use<> {
+        let client = self.clone();
+        numbered_stream(params.page, move |page| {
+            let client = client.clone();
+            let params = ListPartnerProductSalesParams {
+                page,
+                ..params.clone()
+            };
+            async move {
+                let response = client
+                    .list_partner_product_sales(product_id, Some(&params))
+                    .await?;
+                Ok(NumberedPage {
+                    items: response.items,
+                    page: response.page,
+                    size: response.size,
+                    total: response.total,
+                })
+            }
+        })
+    }
+
+    /// Поток всех продуктов и постов (v2), с переходом по ссылкам `nextPage`.
+    pub fn products_stream(
+        &self,
+        params: ListProductsParams,
+    ) -> impl Stream<Item = Result<FeedItemCombined, LavaTopError>> + Send + use<> {
+        let client = self.clone();
+        stream::try_unfold(CursorState::First(params), move |state| {
+            let client = client.clone();
+            async move {
+                let page: PagedResponseV2<FeedItemCombined> = match state {
+                    CursorState::First(params) => client.list_products_v2(Some(&params)).await?,
+                    CursorState::Next(url) => client.fetch_next_page(&url).await?,
+                    CursorState::Done => return Ok::<_, LavaTopError>(None),
+                };
+                let next_state = match page.next_page {
+                    Some(url) if !page.items.is_empty() => CursorState::Next(url),
+                    _ => CursorState::Done,
+                };
+                Ok(Some((page.items, next_state)))
+            }
+        })
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Загружает страницу по ссылке `nextPage` из [`PagedResponseV2`].
+    ///
+    /// Ссылка должна указывать на тот же хост, что и базовый URL клиента,
+    /// иначе API ключ не отправляется и возвращается ошибка.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "lava_top.request",
+            skip_all,
+            fields(method = "GET", endpoint = "nextPage", status = Empty, latency_ms = Empty, attempt = Empty)
+        )
+    )]
+    pub async fn fetch_next_page<T: DeserializeOwned>(
+        &self,
+        next_page: &Url,
+    ) -> Result<PagedResponseV2<T>, LavaTopError> {
+        if next_page.origin() != self.base_url.origin() {
+            return Err(LavaTopError::ForeignNextPage(next_page.clone()));
+        }
+        let response = self
+            // `nextPage` возвращает только каталог продуктов
+            .send_request_to_url::<(), ()>(
+                Method::GET,
+                next_page.clone(),
+                EndpointGroup::Products,
+                None,
+                None,
+            )
+            .await?;
+        self.process_response(response).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use futures::executor::block_on;
+    use std::cell::RefCell;
+
+    /// Собирает поток по набору страниц (нумерация с 1) и возвращает элементы
+    /// вместе с номерами запрошенных страниц.
+    fn collect(
+        start_page: Option<i64>,
+        size: i64,
+        total: i64,
+        pages: Vec<Vec<u32>>,
+    ) -> (Vec<u32>, Vec<Option<i64>>) {
+        let requested = RefCell::new(Vec::new());
+        let items = block_on(
+            numbered_stream(start_page, |page| {
+                requested.borrow_mut().push(page);
+                let number = page.unwrap_or(1);
+                let items = pages.get(number as usize - 1).cloned().unwrap_or_default();
+                async move {
+                    Ok(NumberedPage {
+                        items,
+                        page: number,
+                        size,
+                        total,
+                    })
+                }
+            })
+            .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+        (items, requested.into_inner())
+    }
+
+    #[test]
+    fn stops_on_short_page() {
+        let (items, requested) = collect(None, 2, 100, vec![vec![1, 2], vec![3]]);
+        assert_eq!(items, [1, 2, 3]);
+        assert_eq!(requested, [None, Some(2)]);
+    }
+
+    #[test]
+    fn stops_on_empty_page() {
+        let (items, requested) = collect(None, 2, 100, vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(items, [1, 2, 3, 4]);
+        assert_eq!(requested, [None, Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn stops_after_total_from_first_page() {
+        let (items, requested) = collect(None, 2, 4, vec![vec![1, 2], vec![3, 4], vec![5, 6]]);
+        assert_eq!(items, [1, 2, 3, 4]);
+        assert_eq!(requested, [None, Some(2)]);
+    }
+
+    #[test]
+    fn ignores_total_when_starting_mid_list() {
+        // `total` считает все элементы, а не оставшиеся, поэтому с середины
+        // обход идет до неполной страницы.
+        let (items, requested) = collect(Some(2), 2, 4, vec![vec![1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(items, [3, 4, 5]);
+        assert_eq!(requested, [Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn error_ends_stream() {
+        let mut calls = 0;
+        let results = block_on(
+            numbered_stream(None, |page| {
+                calls += 1;
+                async move {
+                    match page {
+                        None => Ok(NumberedPage {
+                            items: vec![1, 2],
+                            page: 1,
+                            size: 2,
+                            total: 10,
+                        }),
+                        Some(_) => Err(LavaTopError::MissingField("items".to_string())),
+                    }
+                }
+            })
+            .collect::<Vec<Result<u32, _>>>(),
+        );
+        assert_eq!(calls, 2);
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[2], Err(LavaTopError::MissingField(_))));
+    }
+}
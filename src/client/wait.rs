@@ -0,0 +1,410 @@
+use crate::client::LavaTopClient;
+use crate::error::LavaTopError;
+use crate::models::common::InvoiceStatus;
+use crate::models::invoice::InvoiceResponseV2;
+use crate::models::webhook::WebhookEvent;
+use futures::future::{self, Either};
+use futures::stream::{self, Stream, StreamExt};
+use std::pin::pin;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Параметры ожидания оплаты контракта.
+///
+/// Интервал между запросами статуса растет от [`WaitPolicy::interval`] до
+/// [`WaitPolicy::max_interval`]; по истечении [`WaitPolicy::timeout`] ожидание
+/// завершается ошибкой [`LavaTopError::InvoiceWaitTimeout`]. Таймаут, который не
+/// помещается в [`Instant`] (например, [`Duration::MAX`]), означает ожидание без ограничения.
+#[derive(Debug, Clone)]
+pub struct WaitPolicy {
+    interval: Duration,
+    max_interval: Duration,
+    multiplier: f64,
+    timeout: Duration,
+}
+
+impl Default for WaitPolicy {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            max_interval: Duration::from_secs(15),
+            multiplier: 1.5,
+            timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+impl WaitPolicy {
+    /// Политика по умолчанию: от 2 до 15 секунд между запросами, не дольше 10 минут.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Интервал перед вторым запросом статуса.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Максимальный интервал между запросами статуса.
+    pub fn max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Множитель интервала после каждого запроса (не меньше 1; `NaN` заменяется на 1).
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = if multiplier.is_nan() {
+            1.0
+        } else {
+            multiplier.max(1.0)
+        };
+        self
+    }
+
+    /// Сколько всего ждать итогового статуса.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn next_interval(&self, interval: Duration) -> Duration {
+        // Бесконечный или слишком большой результат не помещается в Duration
+        Duration::try_from_secs_f64(interval.as_secs_f64() * self.multiplier)
+            .map_or(self.max_interval, |next| next.min(self.max_interval))
+    }
+}
+
+/// Результат ожидания с вебхуками: статус от API или вебхук об успешной оплате.
+#[derive(Debug, Clone)]
+pub enum WaitOutcome {
+    /// Контракт в том виде, в каком его вернул API.
+    Api(InvoiceResponseV2),
+    /// Вебхук об успешной оплате, пока API еще возвращает `NEW` или `IN_PROGRESS`.
+    Webhook(WebhookEvent),
+}
+
+impl WaitOutcome {
+    /// Контракт от API, если ожидание завершено по его статусу.
+    pub fn invoice(&self) -> Option<&InvoiceResponseV2> {
+        match self {
+            WaitOutcome::Api(invoice) => Some(invoice),
+            WaitOutcome::Webhook(_) => None,
+        }
+    }
+
+    /// Оплачен ли контракт: статус `COMPLETED` от API или вебхук об успешной оплате.
+    pub fn is_paid(&self) -> bool {
+        match self {
+            WaitOutcome::Api(invoice) => invoice.status == InvoiceStatus::Completed,
+            WaitOutcome::Webhook(_) => true,
+        }
+    }
+}
+
+/// Сообщает ли вебхук об успешной оплате. Неуспешная попытка не итоговая: покупатель
+/// может повторить оплату на той же странице.
+fn is_payment_success(event: &WebhookEvent) -> bool {
+    matches!(
+        event,
+        WebhookEvent::PaymentSuccess(_) | WebhookEvent::SubscriptionRecurringPaymentSuccess(_)
+    )
+}
+
+/// Итоговый ли статус: контракт больше не в `NEW` и не в `IN_PROGRESS`.
+fn is_final(status: &InvoiceStatus) -> bool {
+    !matches!(status, InvoiceStatus::New | InvoiceStatus::InProgress)
+}
+
+struct Poller<S> {
+    client: LavaTopClient,
+    invoice_id: Uuid,
+    policy: WaitPolicy,
+    /// `None`, если таймаут не помещается в `Instant`.
+    deadline: Option<Instant>,
+    interval: Duration,
+    last_status: Option<InvoiceStatus>,
+    webhooks: Option<S>,
+    /// Вебхук об успешной оплате, который API еще не успел отразить.
+    paid: Option<WebhookEvent>,
+    first: bool,
+    done: bool,
+}
+
+impl<S: Stream<Item = WebhookEvent> + Unpin> Poller<S> {
+    /// Следующее изменение статуса, ошибка или `None`, если ожидание завершено.
+    async fn next(&mut self) -> Option<Result<WaitOutcome, LavaTopError>> {
+        while !self.done {
+            if !self.first {
+                let remaining = self.deadline.map_or(Duration::MAX, |deadline| {
+                    deadline.saturating_duration_since(Instant::now())
+                });
+                if remaining.is_zero() {
+                    self.done = true;
+                    return Some(Err(LavaTopError::InvoiceWaitTimeout {
+                        invoice_id: self.invoice_id,
+                        last_status: self.last_status.clone(),
+                    }));
+                }
+                self.pause(self.interval.min(remaining)).await;
+                self.interval = self.policy.next_interval(self.interval);
+            }
+            self.first = false;
+
+            let invoice = match self.client.get_invoice_by_id(&self.invoice_id).await {
+                Ok(invoice) => invoice,
+                // Временные ошибки не прерывают ожидание: повторим на следующем шаге
+                Err(error) if error.is_retryable() => match self.paid.take() {
+                    Some(event) => return Some(Ok(self.finish(event))),
+                    None => continue,
+                },
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            };
+            self.done = is_final(&invoice.status);
+            if !self.done
+                && let Some(event) = self.paid.take()
+            {
+                return Some(Ok(self.finish(event)));
+            }
+            if self.last_status.as_ref() != Some(&invoice.status) {
+                self.last_status = Some(invoice.status.clone());
+                return Some(Ok(WaitOutcome::Api(invoice)));
+            }
+        }
+        None
+    }
+
+    fn finish(&mut self, event: WebhookEvent) -> WaitOutcome {
+        self.done = true;
+        WaitOutcome::Webhook(event)
+    }
+
+    /// Ждет `duration` или вебхук по этому контракту, смотря что наступит раньше.
+    async fn pause(&mut self, duration: Duration) {
+        let invoice_id = self.invoice_id;
+        let Some(webhooks) = self.webhooks.as_mut() else {
+            crate::runtime::sleep(duration).await;
+            return;
+        };
+        let sleep = pin!(crate::runtime::sleep(duration));
+        let webhook = pin!(async {
+            while let Some(event) = webhooks.next().await {
+                if event.contract_id() == invoice_id {
+                    return Some(event);
+                }
+            }
+            None
+        });
+        match future::select(sleep, webhook).await {
+            Either::Right((Some(event), _)) => {
+                if is_payment_success(&event) {
+                    self.paid = Some(event);
+                }
+            }
+            Either::Right((None, sleep)) => {
+                // Канал вебхуков закрыт: дожидаемся интервала и дальше только опрашиваем API
+                sleep.await;
+                self.webhooks = None;
+            }
+            Either::Left(_) => {}
+        }
+    }
+}
+
+impl LavaTopClient {
+    /// Ждет, пока контракт выйдет из статусов `NEW` и `IN_PROGRESS`, и возвращает его.
+    ///
+    /// Статус запрашивается через `get_invoice_by_id` с растущим интервалом. Временные
+    /// ошибки (см. [`LavaTopError::is_retryable`]) не прерывают ожидание, остальные
+    /// возвращаются сразу.
+    ///
+    /// ```no_run
+    /// # use lava_top_rs::client::{LavaTopClient, WaitPolicy};
+    /// # use lava_top_rs::models::common::InvoiceStatus;
+    /// # use std::time::Duration;
+    /// # async fn run(client: LavaTopClient, id: uuid::Uuid) -> Result<(), lava_top_rs::error::LavaTopError> {
+    /// let policy = WaitPolicy::new().timeout(Duration::from_secs(300));
+    /// let invoice = client.wait_for_invoice(id, &policy).await?;
+    /// if invoice.status == InvoiceStatus::Completed {
+    ///     println!("оплачено");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_for_invoice(
+        &self,
+        invoice_id: Uuid,
+        policy: &WaitPolicy,
+    ) -> Result<InvoiceResponseV2, LavaTopError> {
+        let mut statuses = pin!(self.invoice_status_stream(invoice_id, policy));
+        let mut last = None;
+        while let Some(invoice) = statuses.next().await {
+            last = Some(invoice?);
+        }
+        // Поток завершается без ошибки только после итогового статуса
+        last.ok_or(LavaTopError::InvoiceWaitTimeout {
+            invoice_id,
+            last_status: None,
+        })
+    }
+
+    /// Как [`Self::wait_for_invoice`], но учитывает вебхуки по этому контракту.
+    ///
+    /// `webhooks` — события, полученные [`WebhookReceiver`](crate::webhook::WebhookReceiver)
+    /// (например, через канал из обработчика). Вебхук с тем же `contract_id` запускает
+    /// внеочередной запрос статуса. Если это вебхук об успешной оплате, а API еще возвращает
+    /// `NEW` или `IN_PROGRESS`, ожидание завершается результатом [`WaitOutcome::Webhook`];
+    /// данные API не изменяются. Вебхук о неуспешной оплате ожидание не завершает: покупатель
+    /// может повторить попытку. Если канал закрыт, ожидание продолжается опросом API.
+    pub async fn wait_for_invoice_with_webhooks<S>(
+        &self,
+        invoice_id: Uuid,
+        policy: &WaitPolicy,
+        webhooks: S,
+    ) -> Result<WaitOutcome, LavaTopError>
+    where
+        S: Stream<Item = WebhookEvent> + Send + Unpin + 'static,
+    {
+        let mut outcomes =
+            pin!(self.invoice_status_stream_with_webhooks(invoice_id, policy, webhooks));
+        let mut last = None;
+        while let Some(outcome) = outcomes.next().await {
+            last = Some(outcome?);
+        }
+        // Поток завершается без ошибки только после итогового статуса
+        last.ok_or(LavaTopError::InvoiceWaitTimeout {
+            invoice_id,
+            last_status: None,
+        })
+    }
+
+    /// Поток изменений статуса контракта.
+    ///
+    /// Первый элемент — текущее состояние контракта, далее — каждое изменение статуса.
+    /// Поток завершается после итогового статуса; по истечении [`WaitPolicy::timeout`]
+    /// последним элементом будет [`LavaTopError::InvoiceWaitTimeout`].
+    pub fn invoice_status_stream(
+        &self,
+        invoice_id: Uuid,
+        policy: &WaitPolicy,
+    ) -> impl Stream<Item = Result<InvoiceResponseV2, LavaTopError>> + Send + use<> {
+        self.invoice_status_stream_with_webhooks(
+            invoice_id,
+            policy,
+            stream::empty::<WebhookEvent>(),
+        )
+        .filter_map(|outcome| {
+            // Без вебхуков результат всегда получен от API
+            future::ready(match outcome {
+                Ok(WaitOutcome::Api(invoice)) => Some(Ok(invoice)),
+                Ok(WaitOutcome::Webhook(_)) => None,
+                Err(error) => Some(Err(error)),
+            })
+        })
+    }
+
+    /// Поток изменений статуса, который опрашивает API сразу после вебхука по контракту.
+    ///
+    /// Вебхук об успешной оплате завершает поток элементом [`WaitOutcome::Webhook`], как в
+    /// [`Self::wait_for_invoice_with_webhooks`].
+    pub fn invoice_status_stream_with_webhooks<S>(
+        &self,
+        invoice_id: Uuid,
+        policy: &WaitPolicy,
+        webhooks: S,
+    ) -> impl Stream<Item = Result<WaitOutcome, LavaTopError>> + Send + use<S>
+    where
+        S: Stream<Item = WebhookEvent> + Send + Unpin + 'static,
+    {
+        let poller = Poller {
+            client: self.clone(),
+            invoice_id,
+            policy: policy.clone(),
+            deadline: Instant::now().checked_add(policy.timeout),
+            interval: policy.interval,
+            last_status: None,
+            webhooks: Some(webhooks),
+            paid: None,
+            first: true,
+            done: false,
+        };
+        stream::unfold(poller, |mut poller| async move {
+            let item = poller.next().await?;
+            Some((item, poller))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn interval_grows_up_to_max() {
+        let policy = WaitPolicy::new().multiplier(2.0).max_interval(SECOND * 5);
+        assert_eq!(policy.next_interval(SECOND), SECOND * 2);
+        assert_eq!(policy.next_interval(SECOND * 2), SECOND * 4);
+        assert_eq!(policy.next_interval(SECOND * 4), SECOND * 5);
+        assert_eq!(policy.next_interval(Duration::MAX), SECOND * 5);
+    }
+
+    #[test]
+    fn multiplier_is_sanitized() {
+        let policy = |multiplier| WaitPolicy::new().multiplier(multiplier);
+        assert_eq!(policy(f64::NAN).multiplier, 1.0);
+        assert_eq!(policy(0.5).multiplier, 1.0);
+        assert_eq!(policy(-3.0).multiplier, 1.0);
+        assert_eq!(policy(f64::NEG_INFINITY).multiplier, 1.0);
+        assert_eq!(policy(f64::NAN).next_interval(SECOND), SECOND);
+
+        let infinite = policy(f64::INFINITY).max_interval(SECOND * 7);
+        assert_eq!(infinite.next_interval(SECOND), SECOND * 7);
+        // 0 * inf = NaN: интервал не помещается в Duration и заменяется максимальным
+        assert_eq!(infinite.next_interval(Duration::ZERO), SECOND * 7);
+    }
+
+    #[test]
+    fn final_statuses() {
+        assert!(!is_final(&InvoiceStatus::New));
+        assert!(!is_final(&InvoiceStatus::InProgress));
+        assert!(is_final(&InvoiceStatus::Completed));
+        assert!(is_final(&InvoiceStatus::Failed));
+        assert!(is_final(&InvoiceStatus::from("REFUNDED")));
+    }
+
+    fn event(event_type: &str) -> WebhookEvent {
+        serde_json::from_value(serde_json::json!({
+            "eventType": event_type,
+            "product": { "id": Uuid::nil(), "title": "Курс" },
+            "contractId": Uuid::nil(),
+            "parentContractId": Uuid::nil(),
+            "buyer": { "email": "buyer@example.com" },
+            "amount": 990.0,
+            "currency": "RUB",
+            "status": "completed",
+            "timestamp": "2024-02-05T09:38:27Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn only_successful_payments_end_wait() {
+        assert!(is_payment_success(&event("payment_success")));
+        assert!(is_payment_success(&event(
+            "subscription_recurring_payment_success"
+        )));
+        assert!(!is_payment_success(&event("payment_failed")));
+        assert!(!is_payment_success(&event(
+            "subscription_recurring_payment_failed"
+        )));
+
+        let outcome = WaitOutcome::Webhook(event("payment_success"));
+        assert!(outcome.is_paid());
+        assert!(outcome.invoice().is_none());
+    }
+}
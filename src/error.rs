@@ -1,7 +1,14 @@
+use crate::client::API_KEY_HEADER;
 use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
 use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
+use url::Url;
+use uuid::Uuid;
 
 /// Структура для деталей ошибки из API Lava Top (соответствует ErrorResponse).
 #[derive(Deserialize, Debug, Clone)]
@@ -12,42 +19,1045 @@ pub struct ApiErrorDetails {
     pub timestamp: Option<DateTime<Utc>>,
 }
 
+/// Ошибка произвольного типа, возвращаемая пользовательским кодом (например, обработчиком вебхуков).
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Максимальный размер тела ответа, сохраняемого в [`LavaTopError::Decode`].
+pub const MAX_DECODE_BODY_LEN: usize = 16 * 1024;
+
+/// Ошибка валидации одного поля запроса.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Имя поля, как его вернул API (например, `email` или `offers[0].price`).
+    pub field: String,
+    /// Текст ошибки: от API или [`FieldError::issue`] на языке по умолчанию.
+    pub message: String,
+    /// Причина ошибки, найденной локальной проверкой; `None` для ошибок от API.
+    pub issue: Option<FieldIssue>,
+}
+
+/// Разобранные детали ошибки валидации (400 или 422).
+///
+/// API возвращает `details` в разных формах: объект `{"поле": "сообщение"}`, объект
+/// со списками сообщений, массив `[{"field": ..., "message": ...}]` или просто строку.
+/// Все они приводятся к списку [`FieldError`] и общему сообщению.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors {
+    /// Общее сообщение об ошибке (`error` или строковый `details`).
+    pub message: Option<String>,
+    pub fields: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    fn from_details(details: &ApiErrorDetails) -> Self {
+        let mut errors = ValidationErrors {
+            message: details.error.clone(),
+            fields: Vec::new(),
+        };
+        match &details.details {
+            Some(Value::Object(map)) => {
+                for (field, value) in map {
+                    errors.push_messages(field, value);
+                }
+            }
+            Some(Value::Array(items)) => {
+                for item in items {
+                    let field = item
+                        .get("field")
+                        .or_else(|| item.get("path"))
+                        .and_then(Value::as_str);
+                    let message = item.get("message").or_else(|| item.get("error"));
+                    match (field, message) {
+                        (Some(field), Some(message)) => errors.push_messages(field, message),
+                        _ => errors.push_messages("", item),
+                    }
+                }
+            }
+            Some(Value::String(message)) if errors.message.is_none() => {
+                errors.message = Some(message.clone());
+            }
+            _ => {}
+        }
+        errors
+    }
+
+    fn push_messages(&mut self, field: &str, value: &Value) {
+        match value {
+            Value::Array(messages) => {
+                for message in messages {
+                    self.push_messages(field, message);
+                }
+            }
+            Value::String(message) => self.fields.push(FieldError {
+                field: field.to_string(),
+                message: message.clone(),
+                issue: None,
+            }),
+            Value::Null => {}
+            other => self.fields.push(FieldError {
+                field: field.to_string(),
+                message: other.to_string(),
+                issue: None,
+            }),
+        }
+    }
+
+    /// Добавляет ошибку поля, найденную локальной проверкой.
+    pub(crate) fn push(&mut self, field: &str, issue: FieldIssue) {
+        self.fields.push(FieldError {
+            field: field.to_string(),
+            message: issue.to_string(),
+            issue: Some(issue),
+        });
+    }
+
+    /// Нет ни одной ошибки.
+    pub fn is_empty(&self) -> bool {
+        self.message.is_none() && self.fields.is_empty()
+    }
+
+    /// Сообщения для поля.
+    pub fn field(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.fields
+            .iter()
+            .filter(move |error| error.field == name)
+            .map(|error| error.message.as_str())
+    }
+
+    /// Сообщения на языке `locale`; тексты от API выводятся как есть.
+    pub fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            error: self,
+            locale,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let mut separator = "";
+        if let Some(message) = &self.message {
+            f.write_str(message)?;
+            separator = "; ";
+        }
+        for error in &self.fields {
+            write!(f, "{separator}{}: ", error.field)?;
+            match &error.issue {
+                Some(issue) => issue.write(f, locale)?,
+                None => f.write_str(&error.message)?,
+            }
+            separator = "; ";
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Locale::default())
+    }
+}
+
+impl fmt::Display for Localized<'_, ValidationErrors> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write(f, self.locale)
+    }
+}
+
+/// Причина ошибки поля, найденной до обращения к API, см. [`FieldError::issue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FieldIssue {
+    /// Адрес почты синтаксически неверен.
+    InvalidEmail(String),
+    /// Идентификатор оффера не задан.
+    MissingOfferId,
+    /// Способ оплаты не поддерживает валюту.
+    UnsupportedCurrency {
+        method: crate::models::common::PaymentMethod,
+        currency: crate::models::common::CurrencyDto,
+    },
+    /// Переданный оффер не совпадает с указанным в запросе.
+    OfferMismatch { expected: Uuid, actual: Uuid },
+    /// У оффера нет цены в валюте.
+    NoPriceInCurrency(crate::models::common::CurrencyDto),
+    /// У оффера нет цены с периодичностью.
+    NoPriceWithPeriodicity(crate::models::common::Periodicity),
+    /// Оффер оплачивается только подпиской, а периодичность не указана.
+    SubscriptionOnly,
+    /// Продукта нет в каталоге.
+    ProductNotFound(crate::checkout::ProductRef),
+    /// В каталоге несколько продуктов с таким названием.
+    AmbiguousProduct(Vec<Uuid>),
+    /// У продукта нет оффера с таким названием.
+    OfferNotFound { product_id: Uuid, offer: String },
+    /// У продукта несколько офферов с таким названием.
+    AmbiguousOffer { product_id: Uuid, offer: String },
+    /// У оффера нет цены в валюте с периодичностью; `available` — имеющиеся цены.
+    NoMatchingPrice {
+        currency: crate::models::common::CurrencyDto,
+        periodicity: crate::models::common::Periodicity,
+        available: Vec<(
+            crate::models::common::CurrencyDto,
+            crate::models::common::Periodicity,
+        )>,
+    },
+}
+
+impl FieldIssue {
+    /// Стабильный машиночитаемый код ошибки, не зависящий от языка.
+    pub fn code(&self) -> &'static str {
+        match self {
+            FieldIssue::InvalidEmail(_) => "field.invalid_email",
+            FieldIssue::MissingOfferId => "field.missing_offer_id",
+            FieldIssue::UnsupportedCurrency { .. } => "field.unsupported_currency",
+            FieldIssue::OfferMismatch { .. } => "field.offer_mismatch",
+            FieldIssue::NoPriceInCurrency(_) => "field.no_price_in_currency",
+            FieldIssue::NoPriceWithPeriodicity(_) => "field.no_price_with_periodicity",
+            FieldIssue::SubscriptionOnly => "field.subscription_only",
+            FieldIssue::ProductNotFound(_) => "field.product_not_found",
+            FieldIssue::AmbiguousProduct(_) => "field.ambiguous_product",
+            FieldIssue::OfferNotFound { .. } => "field.offer_not_found",
+            FieldIssue::AmbiguousOffer { .. } => "field.ambiguous_offer",
+            FieldIssue::NoMatchingPrice { .. } => "field.no_matching_price",
+        }
+    }
+
+    /// Сообщение об ошибке на языке `locale`.
+    pub fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            error: self,
+            locale,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let ru = locale == Locale::Ru;
+        match self {
+            FieldIssue::InvalidEmail(email) if ru => write!(f, "неверный адрес почты: {email:?}"),
+            FieldIssue::InvalidEmail(email) => write!(f, "invalid email address: {email:?}"),
+            FieldIssue::MissingOfferId if ru => f.write_str("идентификатор оффера не задан"),
+            FieldIssue::MissingOfferId => f.write_str("offer id is not set"),
+            FieldIssue::UnsupportedCurrency { method, currency } if ru => {
+                write!(f, "{method} не поддерживает валюту {currency}")
+            }
+            FieldIssue::UnsupportedCurrency { method, currency } => {
+                write!(f, "{method} does not support currency {currency}")
+            }
+            FieldIssue::OfferMismatch { expected, actual } if ru => {
+                write!(
+                    f,
+                    "запрос указывает на оффер {expected}, а передан {actual}"
+                )
+            }
+            FieldIssue::OfferMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "the request refers to offer {expected}, but {actual} was given"
+                )
+            }
+            FieldIssue::NoPriceInCurrency(currency) if ru => {
+                write!(f, "у оффера нет цены в валюте {currency}")
+            }
+            FieldIssue::NoPriceInCurrency(currency) => {
+                write!(f, "the offer has no price in {currency}")
+            }
+            FieldIssue::NoPriceWithPeriodicity(periodicity) if ru => {
+                write!(f, "у оффера нет цены с периодичностью {periodicity}")
+            }
+            FieldIssue::NoPriceWithPeriodicity(periodicity) => {
+                write!(f, "the offer has no price with periodicity {periodicity}")
+            }
+            FieldIssue::SubscriptionOnly if ru => {
+                f.write_str("оффер оплачивается только подпиской, укажите периодичность")
+            }
+            FieldIssue::SubscriptionOnly => {
+                f.write_str("the offer is subscription-only, set a periodicity")
+            }
+            FieldIssue::ProductNotFound(product) => {
+                let name = match product {
+                    crate::checkout::ProductRef::Id(id) => id.to_string(),
+                    crate::checkout::ProductRef::Title(title) => format!("{title:?}"),
+                };
+                if ru {
+                    write!(f, "продукт {name} не найден в каталоге")
+                } else {
+                    write!(f, "product {name} is not in the catalog")
+                }
+            }
+            FieldIssue::AmbiguousProduct(ids) => {
+                let ids = ids
+                    .iter()
+                    .map(Uuid::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if ru {
+                    write!(
+                        f,
+                        "в каталоге несколько продуктов с таким названием, укажите идентификатор: {ids}"
+                    )
+                } else {
+                    write!(
+                        f,
+                        "several catalog products have this title, pass an id instead: {ids}"
+                    )
+                }
+            }
+            FieldIssue::OfferNotFound { product_id, offer } if ru => {
+                write!(f, "у продукта {product_id} нет оффера {offer:?}")
+            }
+            FieldIssue::OfferNotFound { product_id, offer } => {
+                write!(f, "product {product_id} has no offer {offer:?}")
+            }
+            FieldIssue::AmbiguousOffer { product_id, offer } if ru => {
+                write!(f, "у продукта {product_id} несколько офферов {offer:?}")
+            }
+            FieldIssue::AmbiguousOffer { product_id, offer } => {
+                write!(f, "product {product_id} has several offers {offer:?}")
+            }
+            FieldIssue::NoMatchingPrice {
+                currency,
+                periodicity,
+                available,
+            } => {
+                let available = available
+                    .iter()
+                    .map(|(currency, periodicity)| format!("{currency} {periodicity}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if ru {
+                    write!(
+                        f,
+                        "у оффера нет цены {currency} {periodicity}; доступны: {available}"
+                    )
+                } else {
+                    write!(
+                        f,
+                        "the offer has no {currency} {periodicity} price; available: {available}"
+                    )
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for FieldIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Locale::default())
+    }
+}
+
+impl fmt::Display for Localized<'_, FieldIssue> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write(f, self.locale)
+    }
+}
+
+/// Язык сообщений об ошибках.
+///
+/// `Display` у [`LavaTopError`] использует [`Locale::default`]: английский, а с feature `ru`
+/// русский. Другой язык для отдельного сообщения дает [`LavaTopError::localized`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Locale {
+    #[cfg_attr(not(feature = "ru"), default)]
+    En,
+    #[cfg_attr(feature = "ru", default)]
+    Ru,
+}
+
+/// Сообщение об ошибке на выбранном языке, см. [`LavaTopError::localized`].
+#[derive(Debug, Clone, Copy)]
+pub struct Localized<'a, E: ?Sized> {
+    pub(crate) error: &'a E,
+    pub(crate) locale: Locale,
+}
+
+/// Причина отклонения входящего вебхука при проверке подлинности.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WebhookAuthError {
+    /// В запросе нет ни API ключа, ни заголовка Authorization.
+    MissingCredentials,
+    /// Заголовок Authorization имеет неверный формат.
+    MalformedCredentials,
+    /// Учетные данные не совпали ни с одним из допустимых секретов.
+    InvalidCredentials,
+    /// Запрос отклонен пользовательской проверкой.
+    Rejected,
+}
+
+impl WebhookAuthError {
+    /// Стабильный машиночитаемый код ошибки, не зависящий от языка.
+    pub fn code(&self) -> &'static str {
+        match self {
+            WebhookAuthError::MissingCredentials => "webhook_auth.missing_credentials",
+            WebhookAuthError::MalformedCredentials => "webhook_auth.malformed_credentials",
+            WebhookAuthError::InvalidCredentials => "webhook_auth.invalid_credentials",
+            WebhookAuthError::Rejected => "webhook_auth.rejected",
+        }
+    }
+
+    /// Сообщение об ошибке на языке `locale`.
+    pub fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            error: self,
+            locale,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let message = match (self, locale) {
+            (WebhookAuthError::MissingCredentials, Locale::Ru) => "учетные данные отсутствуют",
+            (WebhookAuthError::MissingCredentials, _) => "credentials are missing",
+            (WebhookAuthError::MalformedCredentials, Locale::Ru) => {
+                "неверный формат учетных данных"
+            }
+            (WebhookAuthError::MalformedCredentials, _) => "credentials are malformed",
+            (WebhookAuthError::InvalidCredentials, Locale::Ru) => "неверные учетные данные",
+            (WebhookAuthError::InvalidCredentials, _) => "credentials are invalid",
+            (WebhookAuthError::Rejected, Locale::Ru) => "запрос отклонен проверкой заголовков",
+            (WebhookAuthError::Rejected, _) => "request rejected by header check",
+        };
+        f.write_str(message)
+    }
+}
+
+impl fmt::Display for WebhookAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Locale::default())
+    }
+}
+
+impl fmt::Display for Localized<'_, WebhookAuthError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write(f, self.locale)
+    }
+}
+
+/// Неверная или противоречивая конфигурация клиента, см. [`LavaTopError::InvalidConfig`].
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// Переменная окружения с API ключом не задана.
+    ApiKeyEnvMissing(String),
+    /// Не удалось прочитать файл с API ключом.
+    ApiKeyFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// API ключ передан через `default_headers`.
+    ApiKeyInDefaultHeaders,
+    /// Включен `https_only`, но базовый URL использует другую схему.
+    HttpsOnly { scheme: String },
+    /// Базовый URL не является абсолютным http(s) адресом.
+    BaseUrlNotHttp(Url),
+    /// Базовый URL содержит query или fragment.
+    BaseUrlHasQuery(Url),
+    /// Нулевой таймаут запроса.
+    ZeroTimeout,
+    /// Заданы одновременно `transport` и `http_client`.
+    TransportAndHttpClient,
+    /// Параметры соединения, прокси или TLS заданы вместе с `http_client` или `transport`.
+    ConnectionOptionsWithCustomClient,
+    /// Не удалось создать runtime синхронного клиента.
+    Runtime(#[source] std::io::Error),
+}
+
+impl ConfigError {
+    /// Стабильный машиночитаемый код ошибки, не зависящий от языка.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConfigError::ApiKeyEnvMissing(_) => "config.api_key_env_missing",
+            ConfigError::ApiKeyFile { .. } => "config.api_key_file",
+            ConfigError::ApiKeyInDefaultHeaders => "config.api_key_in_default_headers",
+            ConfigError::HttpsOnly { .. } => "config.https_only",
+            ConfigError::BaseUrlNotHttp(_) => "config.base_url_not_http",
+            ConfigError::BaseUrlHasQuery(_) => "config.base_url_has_query",
+            ConfigError::ZeroTimeout => "config.zero_timeout",
+            ConfigError::TransportAndHttpClient => "config.transport_and_http_client",
+            ConfigError::ConnectionOptionsWithCustomClient => {
+                "config.connection_options_with_custom_client"
+            }
+            ConfigError::Runtime(_) => "config.runtime",
+        }
+    }
+
+    /// Сообщение об ошибке на языке `locale`.
+    pub fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            error: self,
+            locale,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let ru = locale == Locale::Ru;
+        match self {
+            ConfigError::ApiKeyEnvMissing(name) if ru => {
+                write!(f, "переменная окружения {name} с API ключом не задана")
+            }
+            ConfigError::ApiKeyEnvMissing(name) => {
+                write!(f, "API key environment variable {name} is not set")
+            }
+            // Причина (ошибка ввода-вывода) доступна через `source()`
+            ConfigError::ApiKeyFile { path, .. } if ru => {
+                write!(f, "не удалось прочитать API ключ из {}", path.display())
+            }
+            ConfigError::ApiKeyFile { path, .. } => {
+                write!(f, "failed to read API key from {}", path.display())
+            }
+            ConfigError::ApiKeyInDefaultHeaders if ru => write!(
+                f,
+                "заголовок {API_KEY_HEADER} задается через api_key, а не через default_headers"
+            ),
+            ConfigError::ApiKeyInDefaultHeaders => write!(
+                f,
+                "the {API_KEY_HEADER} header is set through api_key, not default_headers"
+            ),
+            ConfigError::HttpsOnly { scheme } if ru => {
+                write!(f, "https_only включен, но базовый URL использует схему {scheme}")
+            }
+            ConfigError::HttpsOnly { scheme } => {
+                write!(f, "https_only is enabled, but the base URL uses scheme {scheme}")
+            }
+            ConfigError::BaseUrlNotHttp(url) if ru => {
+                write!(f, "базовый URL должен быть абсолютным http(s) адресом: {url}")
+            }
+            ConfigError::BaseUrlNotHttp(url) => {
+                write!(f, "base URL must be an absolute http(s) URL: {url}")
+            }
+            ConfigError::BaseUrlHasQuery(url) if ru => {
+                write!(f, "базовый URL не должен содержать query или fragment: {url}")
+            }
+            ConfigError::BaseUrlHasQuery(url) => {
+                write!(f, "base URL must not contain a query or fragment: {url}")
+            }
+            ConfigError::ZeroTimeout if ru => f.write_str("timeout должен быть больше нуля"),
+            ConfigError::ZeroTimeout => f.write_str("timeout must be greater than zero"),
+            ConfigError::TransportAndHttpClient if ru => {
+                f.write_str("transport и http_client нельзя задать одновременно")
+            }
+            ConfigError::TransportAndHttpClient => {
+                f.write_str("transport and http_client cannot both be set")
+            }
+            ConfigError::ConnectionOptionsWithCustomClient if ru => f.write_str(
+                "параметры соединения, прокси и TLS нельзя задать вместе с http_client или transport",
+            ),
+            ConfigError::ConnectionOptionsWithCustomClient => f.write_str(
+                "connection, proxy and TLS options cannot be combined with http_client or transport",
+            ),
+            ConfigError::Runtime(_) if ru => f.write_str("не удалось создать runtime"),
+            ConfigError::Runtime(_) => f.write_str("failed to create runtime"),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Locale::default())
+    }
+}
+
+impl fmt::Display for Localized<'_, ConfigError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write(f, self.locale)
+    }
+}
+
 /// Перечисление ошибок, которые могут возникнуть при работе с Lava Top API.
 #[derive(Error, Debug)]
 pub enum LavaTopError {
     /// Ошибка сети или ошибка во время выполнения HTTP запроса.
-    #[error("Сетевая ошибка или ошибка HTTP запроса: {0}")]
     Reqwest(#[from] reqwest::Error),
 
     /// Ошибка сериализации данных в JSON или десериализации из JSON.
-    #[error("Ошибка сериализации/десериализации JSON: {0}")]
     Serde(#[from] serde_json::Error),
 
     /// Ошибка парсинга URL.
-    #[error("Ошибка парсинга URL: {0}")]
     UrlParse(#[from] url::ParseError),
 
     /// Ошибка создания значения HTTP заголовка.
-    #[error("Неверное значение заголовка: {0}")]
     InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
 
     /// Ошибка, возвращенная API Lava Top (неуспешный HTTP статус).
-    #[error("Ошибка API Lava Top (Статус: {status}): {details:?}{}", raw_body.as_deref().map(|b| format!("\nТело ответа: {}", b)).unwrap_or_default())]
     ApiError {
         status: StatusCode,
         details: Option<ApiErrorDetails>,
         raw_body: Option<String>, // Сохраняем сырое тело на случай ошибки парсинга деталей
+        /// Значение заголовка `Retry-After`, если сервер его прислал.
+        retry_after: Option<Duration>,
+    },
+
+    /// Успешный ответ API не соответствует ожидаемой структуре.
+    ///
+    /// Сохраняет тело ответа (не длиннее [`MAX_DECODE_BODY_LEN`] байт) и путь к полю,
+    /// на котором остановился разбор, например `items[3].offers[0].prices`.
+    Decode {
+        endpoint: String,
+        status: StatusCode,
+        path: String,
+        body: String,
+        #[source]
+        source: serde_json::Error,
     },
 
     /// Отсутствует необходимое поле в ответе API.
-    #[error("Отсутствует обязательное поле в ответе: {0}")]
     MissingField(String),
 
     /// Неверный формат или значение параметра в запросе к API.
-    #[error("Неверный формат параметра запроса: {0}")]
     InvalidQueryParam(String),
 
     /// Не был предоставлен обязательный параметр для вызова метода API.
-    #[error("Отсутствует обязательный параметр в запросе: {0}")]
     MissingParameter(String),
+
+    /// Ссылка `nextPage` ведет на хост, отличный от базового URL клиента.
+    ForeignNextPage(Url),
+
+    /// Попытка запроса не уложилась в таймаут.
+    Timeout(std::time::Duration),
+
+    /// Ошибка, возвращенная пользовательским слоем транспорта.
+    Transport(#[source] BoxError),
+
+    /// Провайдер API ключа вернул ошибку.
+    ApiKey(#[source] BoxError),
+
+    /// Неверная или противоречивая конфигурация клиента.
+    InvalidConfig(#[from] ConfigError),
+
+    /// Запрос не прошел проверку на стороне клиента и не был отправлен.
+    Validation(ValidationErrors),
+
+    /// Контракт не получил итоговый статус за время ожидания.
+    InvoiceWaitTimeout {
+        invoice_id: uuid::Uuid,
+        /// Последний полученный статус, если хотя бы один запрос был успешным.
+        last_status: Option<crate::models::common::InvoiceStatus>,
+    },
+
+    /// Недопустимый переход состояния контракта.
+    InvalidTransition {
+        from: crate::models::contract::ContractState,
+        to: crate::models::contract::ContractState,
+    },
+
+    /// Входящий вебхук не прошел проверку подлинности.
+    WebhookAuth(#[from] WebhookAuthError),
+
+    /// Тело входящего вебхука превышает допустимый размер.
+    WebhookPayloadTooLarge(usize),
+
+    /// Обработчик вебхука вернул ошибку.
+    WebhookHandler(#[source] BoxError),
+}
+
+impl fmt::Display for LavaTopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Locale::default())
+    }
+}
+
+impl fmt::Display for Localized<'_, LavaTopError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write(f, self.locale)
+    }
+}
+
+impl LavaTopError {
+    /// Стабильный машиночитаемый код ошибки, не зависящий от языка.
+    ///
+    /// Для ошибок API код уточняется по статусу (например, `api.unauthorized`).
+    pub fn code(&self) -> &'static str {
+        match self {
+            LavaTopError::Reqwest(_) => "http",
+            LavaTopError::Serde(_) => "json",
+            LavaTopError::UrlParse(_) => "url_parse",
+            LavaTopError::InvalidHeaderValue(_) => "invalid_header_value",
+            LavaTopError::ApiError { status, .. } => match *status {
+                StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => "api.validation",
+                StatusCode::UNAUTHORIZED => "api.unauthorized",
+                StatusCode::FORBIDDEN => "api.forbidden",
+                StatusCode::NOT_FOUND => "api.not_found",
+                StatusCode::TOO_MANY_REQUESTS => "api.rate_limited",
+                status if status.is_server_error() => "api.server_error",
+                _ => "api.error",
+            },
+            LavaTopError::Decode { .. } => "decode",
+            LavaTopError::MissingField(_) => "missing_field",
+            LavaTopError::InvalidQueryParam(_) => "invalid_query_param",
+            LavaTopError::MissingParameter(_) => "missing_parameter",
+            LavaTopError::ForeignNextPage(_) => "foreign_next_page",
+            LavaTopError::Timeout(_) => "timeout",
+            LavaTopError::Transport(_) => "transport",
+            LavaTopError::ApiKey(_) => "api_key",
+            LavaTopError::InvalidConfig(error) => error.code(),
+            LavaTopError::Validation(_) => "validation",
+            LavaTopError::InvoiceWaitTimeout { .. } => "invoice_wait_timeout",
+            LavaTopError::InvalidTransition { .. } => "invalid_transition",
+            LavaTopError::WebhookAuth(error) => error.code(),
+            LavaTopError::WebhookPayloadTooLarge(_) => "webhook.payload_too_large",
+            LavaTopError::WebhookHandler(_) => "webhook.handler",
+        }
+    }
+
+    /// Сообщение об ошибке на языке `locale`, независимо от [`Locale::default`].
+    ///
+    /// ```
+    /// # use lava_top_rs::error::{LavaTopError, Locale};
+    /// let error = LavaTopError::MissingParameter("api_key".to_string());
+    /// assert_eq!(
+    ///     error.localized(Locale::En).to_string(),
+    ///     "Missing required request parameter: api_key"
+    /// );
+    /// assert_eq!(error.code(), "missing_parameter");
+    /// ```
+    pub fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            error: self,
+            locale,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let ru = locale == Locale::Ru;
+        match self {
+            LavaTopError::Reqwest(e) if ru => {
+                write!(f, "Сетевая ошибка или ошибка HTTP запроса: {e}")
+            }
+            LavaTopError::Reqwest(e) => write!(f, "Network or HTTP request error: {e}"),
+            LavaTopError::Serde(e) if ru => {
+                write!(f, "Ошибка сериализации/десериализации JSON: {e}")
+            }
+            LavaTopError::Serde(e) => write!(f, "JSON serialization/deserialization error: {e}"),
+            LavaTopError::UrlParse(e) if ru => write!(f, "Ошибка парсинга URL: {e}"),
+            LavaTopError::UrlParse(e) => write!(f, "URL parse error: {e}"),
+            LavaTopError::InvalidHeaderValue(e) if ru => {
+                write!(f, "Неверное значение заголовка: {e}")
+            }
+            LavaTopError::InvalidHeaderValue(e) => write!(f, "Invalid header value: {e}"),
+            LavaTopError::ApiError {
+                status,
+                details,
+                raw_body,
+                ..
+            } => {
+                if ru {
+                    write!(f, "Ошибка API Lava Top (Статус: {status}): {details:?}")?;
+                } else {
+                    write!(f, "Lava Top API error (status: {status}): {details:?}")?;
+                }
+                match raw_body {
+                    Some(body) if ru => write!(f, "\nТело ответа: {body}"),
+                    Some(body) => write!(f, "\nResponse body: {body}"),
+                    None => Ok(()),
+                }
+            }
+            LavaTopError::Decode {
+                endpoint,
+                status,
+                path,
+                source,
+                ..
+            } if ru => write!(
+                f,
+                "Не удалось разобрать ответ {endpoint} (Статус: {status}) в поле `{path}`: {source}"
+            ),
+            LavaTopError::Decode {
+                endpoint,
+                status,
+                path,
+                source,
+                ..
+            } => write!(
+                f,
+                "Failed to decode response from {endpoint} (status: {status}) at `{path}`: {source}"
+            ),
+            LavaTopError::MissingField(field) if ru => {
+                write!(f, "Отсутствует обязательное поле в ответе: {field}")
+            }
+            LavaTopError::MissingField(field) => {
+                write!(f, "Missing required field in response: {field}")
+            }
+            LavaTopError::InvalidQueryParam(e) if ru => {
+                write!(f, "Неверный формат параметра запроса: {e}")
+            }
+            LavaTopError::InvalidQueryParam(e) => write!(f, "Invalid query parameter: {e}"),
+            LavaTopError::MissingParameter(name) if ru => {
+                write!(f, "Отсутствует обязательный параметр в запросе: {name}")
+            }
+            LavaTopError::MissingParameter(name) => {
+                write!(f, "Missing required request parameter: {name}")
+            }
+            LavaTopError::ForeignNextPage(url) if ru => {
+                write!(f, "nextPage указывает на сторонний хост: {url}")
+            }
+            LavaTopError::ForeignNextPage(url) => {
+                write!(f, "nextPage points to a different host: {url}")
+            }
+            LavaTopError::Timeout(timeout) if ru => {
+                write!(f, "Превышено время ожидания ответа ({timeout:?})")
+            }
+            LavaTopError::Timeout(timeout) => write!(f, "Request timed out ({timeout:?})"),
+            LavaTopError::Transport(e) if ru => write!(f, "Ошибка транспорта: {e}"),
+            LavaTopError::Transport(e) => write!(f, "Transport error: {e}"),
+            LavaTopError::ApiKey(e) if ru => write!(f, "Не удалось получить API ключ: {e}"),
+            LavaTopError::ApiKey(e) => write!(f, "Failed to obtain API key: {e}"),
+            LavaTopError::InvalidConfig(e) if ru => {
+                write!(f, "Неверная конфигурация клиента: {}", e.localized(locale))
+            }
+            LavaTopError::InvalidConfig(e) => {
+                write!(f, "Invalid client configuration: {}", e.localized(locale))
+            }
+            LavaTopError::Validation(errors) if ru => {
+                write!(f, "Запрос не прошел проверку: {}", errors.localized(locale))
+            }
+            LavaTopError::Validation(errors) => {
+                write!(f, "Request validation failed: {}", errors.localized(locale))
+            }
+            LavaTopError::InvoiceWaitTimeout {
+                invoice_id,
+                last_status,
+            } => {
+                let status = last_status
+                    .as_ref()
+                    .map_or_else(|| "?".to_string(), ToString::to_string);
+                if ru {
+                    write!(
+                        f,
+                        "Контракт {invoice_id} не получил итоговый статус за время ожидания (статус: {status})"
+                    )
+                } else {
+                    write!(
+                        f,
+                        "Invoice {invoice_id} did not reach a final status in time (status: {status})"
+                    )
+                }
+            }
+            LavaTopError::InvalidTransition { from, to } if ru => {
+                write!(f, "Недопустимый переход контракта: {from} -> {to}")
+            }
+            LavaTopError::InvalidTransition { from, to } => {
+                write!(f, "Invalid contract transition: {from} -> {to}")
+            }
+            LavaTopError::WebhookAuth(e) if ru => write!(
+                f,
+                "Вебхук не прошел аутентификацию: {}",
+                e.localized(locale)
+            ),
+            LavaTopError::WebhookAuth(e) => {
+                write!(f, "Webhook authentication failed: {}", e.localized(locale))
+            }
+            LavaTopError::WebhookPayloadTooLarge(limit) if ru => {
+                write!(f, "Тело вебхука превышает допустимый размер ({limit} байт)")
+            }
+            LavaTopError::WebhookPayloadTooLarge(limit) => {
+                write!(f, "Webhook body exceeds the size limit ({limit} bytes)")
+            }
+            LavaTopError::WebhookHandler(e) if ru => write!(f, "Ошибка обработчика вебхука: {e}"),
+            LavaTopError::WebhookHandler(e) => write!(f, "Webhook handler error: {e}"),
+        }
+    }
+
+    /// Ошибка API из неуспешного ответа: детали разбираются, если тело в формате ErrorResponse.
+    pub(crate) fn from_response(response: &crate::transport::HttpResponse) -> Self {
+        let raw_body = Some(String::from_utf8_lossy(response.body()).into_owned());
+        // Пытаемся парсить как стандартную структуру ошибки API
+        let details: Option<ApiErrorDetails> = raw_body
+            .as_deref()
+            .and_then(|body| serde_json::from_str(body).ok());
+        LavaTopError::ApiError {
+            status: response.status(),
+            details,
+            raw_body,
+            retry_after: crate::retry::parse_retry_after(response.headers()),
+        }
+    }
+
+    /// HTTP статус ответа API, если ошибка вызвана ответом сервера.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            LavaTopError::ApiError { status, .. } | LavaTopError::Decode { status, .. } => {
+                Some(*status)
+            }
+            _ => None,
+        }
+    }
+
+    /// Временная ли ошибка: повтор того же запроса позже может завершиться успехом.
+    ///
+    /// Сюда относятся таймауты, ошибки соединения и статусы 408, 429, 500, 502, 503 и 504.
+    /// Идемпотентность запроса не учитывается: повторять создание контракта после
+    /// таймаута небезопасно, если сервер мог успеть его обработать.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LavaTopError::ApiError { status, .. } => {
+                crate::retry::RETRYABLE_STATUSES.contains(status)
+            }
+            LavaTopError::Reqwest(error) => {
+                error.is_connect() || error.is_timeout() || error.is_request()
+            }
+            LavaTopError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Неверный, отозванный или не имеющий доступа API ключ (401 или 403).
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self.api_status(),
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        )
+    }
+
+    /// Запрошенный объект не найден (404).
+    pub fn is_not_found(&self) -> bool {
+        self.api_status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Параметры запроса отклонены: API (400 или 422) или проверкой на стороне клиента.
+    ///
+    /// Подробности по полям доступны через [`Self::validation_errors`].
+    pub fn is_validation_error(&self) -> bool {
+        matches!(self, LavaTopError::Validation(_))
+            || matches!(
+                self.api_status(),
+                Some(StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY)
+            )
+    }
+
+    /// Превышен лимит запросов (429).
+    pub fn is_rate_limited(&self) -> bool {
+        self.api_status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Через сколько сервер разрешил повторить запрос (заголовок `Retry-After`).
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LavaTopError::ApiError { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Ошибки валидации по полям, если это ошибка валидации.
+    pub fn validation_errors(&self) -> Option<ValidationErrors> {
+        if !self.is_validation_error() {
+            return None;
+        }
+        match self {
+            LavaTopError::Validation(errors) => Some(errors.clone()),
+            LavaTopError::ApiError {
+                details: Some(details),
+                ..
+            } => Some(ValidationErrors::from_details(details)),
+            _ => Some(ValidationErrors::default()),
+        }
+    }
+
+    /// Статус неуспешного ответа API (без [`LavaTopError::Decode`], где статус успешный).
+    fn api_status(&self) -> Option<StatusCode> {
+        match self {
+            LavaTopError::ApiError { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Ошибка разбора успешного ответа с путем к проблемному полю.
+    pub(crate) fn decode(
+        endpoint: impl Into<String>,
+        status: StatusCode,
+        body: &[u8],
+        error: serde_path_to_error::Error<serde_json::Error>,
+    ) -> Self {
+        let path = error.path().to_string();
+        let mut text = String::from_utf8_lossy(body).into_owned();
+        if text.len() > MAX_DECODE_BODY_LEN {
+            let mut end = MAX_DECODE_BODY_LEN;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+            text.push_str(&format!("… ({} B)", body.len()));
+        }
+        LavaTopError::Decode {
+            endpoint: endpoint.into(),
+            status,
+            path,
+            body: text,
+            source: error.into_inner(),
+        }
+    }
+
+    /// Восстанавливает ошибку из результата транспорта: ошибки reqwest и самой библиотеки
+    /// сохраняют свой вариант, остальные оборачиваются в [`LavaTopError::Transport`].
+    pub(crate) fn from_transport(error: BoxError) -> Self {
+        let error = match error.downcast::<LavaTopError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        match error.downcast::<reqwest::Error>() {
+            Ok(error) => LavaTopError::Reqwest(*error),
+            Err(error) => LavaTopError::Transport(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_error(json: &str) -> serde_path_to_error::Error<serde_json::Error> {
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Item {
+            amount: f64,
+        }
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Page {
+            items: Vec<Item>,
+        }
+        let deserializer = &mut serde_json::Deserializer::from_str(json);
+        serde_path_to_error::deserialize::<_, Page>(deserializer).unwrap_err()
+    }
+
+    #[test]
+    fn decode_keeps_endpoint_status_and_path() {
+        let body = r#"{"items":[{"amount":1},{"amount":"x"}]}"#;
+        let error = LavaTopError::decode(
+            "/api/v1/invoices",
+            StatusCode::OK,
+            body.as_bytes(),
+            path_error(body),
+        );
+        let LavaTopError::Decode {
+            endpoint,
+            status,
+            path,
+            body: kept,
+            ..
+        } = &error
+        else {
+            panic!("ожидалась ошибка Decode");
+        };
+        assert_eq!(endpoint, "/api/v1/invoices");
+        assert_eq!(*status, StatusCode::OK);
+        assert_eq!(path, "items[1].amount");
+        assert_eq!(kept, body);
+        assert_eq!(error.code(), "decode");
+    }
+
+    #[test]
+    fn decode_caps_body_on_char_boundary() {
+        // Двухбайтовые символы: граница MAX_DECODE_BODY_LEN попадает внутрь символа
+        let body = format!(r#"{{"items":"{}"}}"#, "я".repeat(MAX_DECODE_BODY_LEN));
+        let error = LavaTopError::decode("", StatusCode::OK, body.as_bytes(), path_error(&body));
+        let LavaTopError::Decode { body: kept, .. } = error else {
+            panic!("ожидалась ошибка Decode");
+        };
+        let suffix = format!("… ({} B)", body.len());
+        assert!(kept.ends_with(&suffix));
+        let prefix = kept.strip_suffix(&suffix).unwrap();
+        assert!(prefix.len() <= MAX_DECODE_BODY_LEN);
+        assert!(prefix.len() > MAX_DECODE_BODY_LEN - 4);
+        assert!(body.starts_with(prefix));
+    }
 }
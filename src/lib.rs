@@ -1,3 +1,17 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod checkout;
 pub mod client;
 pub mod error;
 pub mod models;
+pub mod rate_limit;
+#[cfg(feature = "tracing")]
+mod redact;
+pub mod retry;
+mod runtime;
+pub mod secret;
+pub mod subscriptions;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
+pub mod webhook;
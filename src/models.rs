@@ -1,7 +1,10 @@
 pub mod common;
+pub mod contract;
 pub mod donate;
 pub mod feed;
 pub mod invoice;
+#[cfg(feature = "money")]
+pub mod money;
 pub mod product;
 pub mod report;
 pub mod subscription;
@@ -1,43 +1,132 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Months, Utc};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 // --- Enum Определения ---
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum CurrencyDto {
-    #[default]
-    Rub,
-    Usd,
-    Eur,
+
+/// Объявляет перечисление значений API с запасным вариантом `Unknown(String)`.
+///
+/// Значения, появившиеся в API после выхода этой версии библиотеки, попадают в `Unknown`
+/// и сериализуются обратно без изменений. С feature `strict-enums` такие значения
+/// вызывают ошибку десериализации.
+macro_rules! api_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$vmeta:meta])*
+                $variant:ident = $value:literal
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $( $(#[$vmeta])* $variant, )+
+            /// Значение, неизвестное этой версии библиотеки.
+            Unknown(String),
+        }
+
+        impl $name {
+            /// Значение в формате API.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $( Self::$variant => $value, )+
+                    Self::Unknown(value) => value,
+                }
+            }
+
+            /// Является ли значение неизвестным этой версии библиотеки.
+            pub fn is_unknown(&self) -> bool {
+                matches!(self, Self::Unknown(_))
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $( $value => Self::$variant, )+
+                    _ => Self::Unknown(value.to_string()),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = std::borrow::Cow::<'de, str>::deserialize(deserializer)?;
+                let parsed = Self::from(value.as_ref());
+                if cfg!(feature = "strict-enums") && parsed.is_unknown() {
+                    return Err(serde::de::Error::unknown_variant(&value, &[$($value),+]));
+                }
+                Ok(parsed)
+            }
+        }
+    };
+}
+
+pub(crate) use api_enum;
+
+api_enum! {
+    #[derive(Default)]
+    pub enum CurrencyDto {
+        #[default]
+        Rub = "RUB",
+        Usd = "USD",
+        Eur = "EUR",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum LanguageDto {
-    #[default]
-    En,
-    Ru,
-    Es,
+impl CurrencyDto {
+    /// Количество знаков после запятой в минимальной единице валюты (копейка, цент).
+    ///
+    /// Для неизвестных валют предполагается 2 знака.
+    #[must_use]
+    pub fn minor_units(&self) -> u32 {
+        match self {
+            Self::Rub | Self::Usd | Self::Eur | Self::Unknown(_) => 2,
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum PaymentMethod {
-    Bank131,
-    Unlimint,
-    Paypal,
-    Stripe,
+api_enum! {
+    #[derive(Default)]
+    pub enum LanguageDto {
+        #[default]
+        En = "EN",
+        Ru = "RU",
+        Es = "ES",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum Periodicity {
-    OneTime,
-    Monthly,
-    Period90Days,
-    Period180Days,
-    PeriodYear,
+api_enum! {
+    pub enum PaymentMethod {
+        Bank131 = "BANK131",
+        Unlimint = "UNLIMINT",
+        Paypal = "PAYPAL",
+        Stripe = "STRIPE",
+    }
+}
+
+api_enum! {
+    pub enum Periodicity {
+        OneTime = "ONE_TIME",
+        Monthly = "MONTHLY",
+        Period90Days = "PERIOD90_DAYS",
+        Period180Days = "PERIOD180_DAYS",
+        PeriodYear = "PERIOD_YEAR",
+    }
 }
 
 impl Periodicity {
@@ -51,119 +140,119 @@ impl Periodicity {
             _ => Self::OneTime,
         }
     }
-}
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "kebab-case")]
-pub enum ContractStatusDto {
-    New,
-    // #[serde(rename = "in-progress")]
-    InProgress,
-    Completed,
-    Failed,
-    Cancelled,
-    SubscriptionActive,
-    SubscriptionExpired,
-    SubscriptionCancelled,
-    SubscriptionFailed,
-}
-
-impl std::fmt::Display for ContractStatusDto {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        let json_string = serde_json::to_string(&self).unwrap();
-        let s = json_string.trim_matches('"');
-        write!(f, "{s}")
+    /// Конец периода подписки, начавшегося в `start`; `None` для разовой оплаты и
+    /// неизвестных значений.
+    ///
+    /// Месяц и год считаются по календарю (31 января + месяц = 28 или 29 февраля),
+    /// 90 и 180 дней — точным числом дней.
+    #[must_use]
+    pub fn period_end(&self, start: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Monthly => start.checked_add_months(Months::new(1)),
+            Self::Period90Days => start.checked_add_signed(Duration::days(90)),
+            Self::Period180Days => start.checked_add_signed(Duration::days(180)),
+            Self::PeriodYear => start.checked_add_months(Months::new(12)),
+            Self::OneTime | Self::Unknown(_) => None,
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum InvoiceStatus {
-    New,
-    InProgress,
-    Completed,
-    Failed,
+api_enum! {
+    pub enum ContractStatusDto {
+        New = "new",
+        InProgress = "in-progress",
+        Completed = "completed",
+        Failed = "failed",
+        Cancelled = "cancelled",
+        SubscriptionActive = "subscription-active",
+        SubscriptionExpired = "subscription-expired",
+        SubscriptionCancelled = "subscription-cancelled",
+        SubscriptionFailed = "subscription-failed",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum InvoiceType {
-    OneTime,
-    Recurring,
+api_enum! {
+    pub enum InvoiceStatus {
+        New = "NEW",
+        InProgress = "IN_PROGRESS",
+        Completed = "COMPLETED",
+        Failed = "FAILED",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum SubscriptionStatus {
-    Active,
-    Cancelled,
-    Failed,
+api_enum! {
+    pub enum InvoiceType {
+        OneTime = "ONE_TIME",
+        Recurring = "RECURRING",
+    }
 }
 
-impl std::fmt::Display for SubscriptionStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        let json_string = serde_json::to_string(&self).unwrap();
-        let s = json_string.trim_matches('"');
-        write!(f, "{s}")
+api_enum! {
+    pub enum SubscriptionStatus {
+        Active = "ACTIVE",
+        Cancelled = "CANCELLED",
+        Failed = "FAILED",
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum FeedItemType {
-    Post,
-    Product,
+api_enum! {
+    pub enum FeedItemType {
+        Post = "POST",
+        Product = "PRODUCT",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum ProductType {
-    Course,
-    DigitalProduct,
-    Book,
-    Guide,
-    Subscription,
-    Audio,
-    Mods,
-    Consultation,
+api_enum! {
+    pub enum ProductType {
+        Course = "COURSE",
+        DigitalProduct = "DIGITAL_PRODUCT",
+        Book = "BOOK",
+        Guide = "GUIDE",
+        Subscription = "SUBSCRIPTION",
+        Audio = "AUDIO",
+        Mods = "MODS",
+        Consultation = "CONSULTATION",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum FeedVisibility {
-    All,
-    #[default]
-    OnlyVisible,
-    OnlyHidden,
+api_enum! {
+    #[derive(Default)]
+    pub enum FeedVisibility {
+        All = "ALL",
+        #[default]
+        OnlyVisible = "ONLY_VISIBLE",
+        OnlyHidden = "ONLY_HIDDEN",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum PostType {
-    Lesson,
-    Post,
+api_enum! {
+    pub enum PostType {
+        Lesson = "LESSON",
+        Post = "POST",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum Status {
-    Published,
+api_enum! {
+    pub enum Status {
+        Published = "PUBLISHED",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum ModerationStatus {
-    New,
-    Rejected,
-    Approved,
-    Blocked,
+api_enum! {
+    pub enum ModerationStatus {
+        New = "NEW",
+        Rejected = "REJECTED",
+        Approved = "APPROVED",
+        Blocked = "BLOCKED",
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum DeleteNotAllowedReason {
-    HasSales,
-    HasPosts,
+api_enum! {
+    pub enum DeleteNotAllowedReason {
+        HasSales = "HAS_SALES",
+        HasPosts = "HAS_POSTS",
+    }
 }
 
 // --- Общие Структуры ---
@@ -177,6 +266,13 @@ pub struct PriceDto {
     pub periodicity: Option<Periodicity>,
 }
 
+impl PriceDto {
+    /// Периодичность цены; цена без периодичности считается разовой.
+    pub(crate) fn periodicity_or_one_time(&self) -> Periodicity {
+        self.periodicity.clone().unwrap_or(Periodicity::OneTime)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ClientUtmDto {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -245,7 +341,7 @@ pub(crate) mod opt_chrono_datetime_as_iso8601 {
 
 pub(crate) mod opt_chrono_naive_date_as_str {
     use chrono::NaiveDate;
-    use serde::{Serialize, Serializer};
+    use serde::Serializer;
     const FORMAT: &str = "%Y-%m-%d";
 
     pub fn serialize<S>(date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error>
@@ -258,3 +354,99 @@ pub(crate) mod opt_chrono_naive_date_as_str {
         }
     }
 }
+
+pub(crate) mod opt_vec_as_comma_separated {
+    use serde::{Serialize, Serializer};
+
+    pub fn serialize<T, S>(values: &Option<Vec<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: std::fmt::Display,
+        S: Serializer,
+    {
+        match values {
+            Some(values) => values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+                .serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 12, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn monthly_clamps_to_month_end() {
+        let monthly = Periodicity::Monthly;
+        assert_eq!(
+            monthly.period_end(date(2023, 1, 31)),
+            Some(date(2023, 2, 28))
+        );
+        assert_eq!(
+            monthly.period_end(date(2024, 1, 31)),
+            Some(date(2024, 2, 29))
+        );
+        assert_eq!(
+            monthly.period_end(date(2024, 3, 31)),
+            Some(date(2024, 4, 30))
+        );
+        assert_eq!(
+            monthly.period_end(date(2024, 12, 15)),
+            Some(date(2025, 1, 15))
+        );
+    }
+
+    #[test]
+    fn year_clamps_leap_day() {
+        let year = Periodicity::PeriodYear;
+        assert_eq!(year.period_end(date(2024, 2, 29)), Some(date(2025, 2, 28)));
+        assert_eq!(year.period_end(date(2023, 3, 1)), Some(date(2024, 3, 1)));
+    }
+
+    #[test]
+    fn fixed_day_periods() {
+        assert_eq!(
+            Periodicity::Period90Days.period_end(date(2024, 1, 1)),
+            Some(date(2024, 3, 31))
+        );
+        assert_eq!(
+            Periodicity::Period180Days.period_end(date(2023, 1, 1)),
+            Some(date(2023, 6, 30))
+        );
+    }
+
+    #[test]
+    fn no_period_end() {
+        assert_eq!(Periodicity::OneTime.period_end(date(2024, 1, 1)), None);
+        assert_eq!(
+            Periodicity::from("PERIOD_WEEK").period_end(date(2024, 1, 1)),
+            None
+        );
+        assert_eq!(
+            Periodicity::Monthly.period_end(DateTime::<Utc>::MAX_UTC),
+            None
+        );
+    }
+
+    #[test]
+    fn unknown_values() {
+        let week = Periodicity::from("PERIOD_WEEK");
+        assert!(week.is_unknown());
+        assert_eq!(serde_json::to_string(&week).unwrap(), r#""PERIOD_WEEK""#);
+        let parsed = serde_json::from_str::<Periodicity>(r#""PERIOD_WEEK""#);
+        if cfg!(feature = "strict-enums") {
+            assert!(parsed.is_err());
+        } else {
+            assert_eq!(parsed.unwrap(), week);
+        }
+    }
+}
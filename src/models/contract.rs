@@ -0,0 +1,324 @@
+use crate::error::LavaTopError;
+use crate::models::common::{ContractStatusDto, InvoiceStatus, SubscriptionStatus};
+use std::fmt;
+
+/// Состояние контракта, общее для всех эндпоинтов и вебхуков.
+///
+/// API описывает один и тот же жизненный цикл тремя перечислениями:
+/// [`ContractStatusDto`] (вебхуки и отчеты), [`InvoiceStatus`] (`get_invoice_by_id`)
+/// и [`SubscriptionStatus`]. Каждое из них без потерь преобразуется в `ContractState`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ContractState {
+    New,
+    InProgress,
+    Completed,
+    Failed,
+    Cancelled,
+    SubscriptionActive,
+    SubscriptionExpired,
+    SubscriptionCancelled,
+    SubscriptionFailed,
+    /// Значение, неизвестное этой версии библиотеки.
+    Unknown(String),
+}
+
+const FROM_NEW: &[ContractState] = &[
+    ContractState::InProgress,
+    ContractState::Completed,
+    ContractState::Failed,
+    ContractState::Cancelled,
+    ContractState::SubscriptionActive,
+];
+const FROM_IN_PROGRESS: &[ContractState] = &[
+    ContractState::Completed,
+    ContractState::Failed,
+    ContractState::Cancelled,
+    ContractState::SubscriptionActive,
+];
+const FROM_SUBSCRIPTION_ACTIVE: &[ContractState] = &[
+    ContractState::SubscriptionExpired,
+    ContractState::SubscriptionCancelled,
+    ContractState::SubscriptionFailed,
+];
+const FROM_SUBSCRIPTION_FAILED: &[ContractState] = &[
+    ContractState::SubscriptionActive,
+    ContractState::SubscriptionExpired,
+    ContractState::SubscriptionCancelled,
+];
+const FROM_SUBSCRIPTION_CANCELLED: &[ContractState] = &[ContractState::SubscriptionExpired];
+
+impl ContractState {
+    /// Значение в формате [`ContractStatusDto`].
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::New => "new",
+            Self::InProgress => "in-progress",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::SubscriptionActive => "subscription-active",
+            Self::SubscriptionExpired => "subscription-expired",
+            Self::SubscriptionCancelled => "subscription-cancelled",
+            Self::SubscriptionFailed => "subscription-failed",
+            Self::Unknown(value) => value,
+        }
+    }
+
+    /// Является ли значение неизвестным этой версии библиотеки.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+
+    /// Состояния, в которые контракт может перейти из текущего.
+    ///
+    /// Для [`ContractState::Unknown`] таблица неизвестна и список пуст.
+    pub fn next_states(&self) -> &'static [ContractState] {
+        match self {
+            Self::New => FROM_NEW,
+            Self::InProgress => FROM_IN_PROGRESS,
+            Self::SubscriptionActive => FROM_SUBSCRIPTION_ACTIVE,
+            Self::SubscriptionFailed => FROM_SUBSCRIPTION_FAILED,
+            Self::SubscriptionCancelled => FROM_SUBSCRIPTION_CANCELLED,
+            Self::Completed
+            | Self::Failed
+            | Self::Cancelled
+            | Self::SubscriptionExpired
+            | Self::Unknown(_) => &[],
+        }
+    }
+
+    /// Возможен ли переход в `next`.
+    ///
+    /// Повтор текущего состояния (например, дубль вебхука) допустим. Переходы из
+    /// неизвестного состояния или в него не проверяются.
+    pub fn can_transition_to(&self, next: &ContractState) -> bool {
+        self == next || self.is_unknown() || next.is_unknown() || self.next_states().contains(next)
+    }
+
+    /// Достижимо ли `target` по таблице переходов за один или несколько шагов.
+    ///
+    /// В отличие от [`Self::can_transition_to`] допускает пропущенные промежуточные
+    /// состояния (например, `new` -> `subscription-expired` для снимка из API), но так же
+    /// отвергает возврат к более раннему состоянию.
+    pub fn can_reach(&self, target: &ContractState) -> bool {
+        if self.can_transition_to(target) {
+            return true;
+        }
+        let mut seen = vec![self];
+        let mut pending: Vec<&ContractState> = self.next_states().iter().collect();
+        while let Some(state) = pending.pop() {
+            if state == target {
+                return true;
+            }
+            if !seen.contains(&state) {
+                seen.push(state);
+                pending.extend(state.next_states());
+            }
+        }
+        false
+    }
+
+    /// Переводит контракт в `next` или возвращает [`LavaTopError::InvalidTransition`].
+    pub fn transition(self, next: ContractState) -> Result<ContractState, LavaTopError> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(LavaTopError::InvalidTransition {
+                from: self,
+                to: next,
+            })
+        }
+    }
+
+    /// Итоговое состояние: дальнейшие переходы невозможны.
+    pub fn is_terminal(&self) -> bool {
+        !self.is_unknown() && self.next_states().is_empty()
+    }
+
+    /// Контракт оплачен и доступ открыт: разовая покупка завершена или подписка
+    /// действует (в том числе отмененная до конца оплаченного периода).
+    pub fn is_paid(&self) -> bool {
+        matches!(
+            self,
+            Self::Completed | Self::SubscriptionActive | Self::SubscriptionCancelled
+        )
+    }
+
+    /// Состояние подписки.
+    pub fn is_subscription(&self) -> bool {
+        matches!(
+            self,
+            Self::SubscriptionActive
+                | Self::SubscriptionExpired
+                | Self::SubscriptionCancelled
+                | Self::SubscriptionFailed
+        )
+    }
+}
+
+impl fmt::Display for ContractState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ContractStatusDto> for ContractState {
+    fn from(status: ContractStatusDto) -> Self {
+        match status {
+            ContractStatusDto::New => ContractState::New,
+            ContractStatusDto::InProgress => ContractState::InProgress,
+            ContractStatusDto::Completed => ContractState::Completed,
+            ContractStatusDto::Failed => ContractState::Failed,
+            ContractStatusDto::Cancelled => ContractState::Cancelled,
+            ContractStatusDto::SubscriptionActive => ContractState::SubscriptionActive,
+            ContractStatusDto::SubscriptionExpired => ContractState::SubscriptionExpired,
+            ContractStatusDto::SubscriptionCancelled => ContractState::SubscriptionCancelled,
+            ContractStatusDto::SubscriptionFailed => ContractState::SubscriptionFailed,
+            ContractStatusDto::Unknown(value) => ContractState::Unknown(value),
+        }
+    }
+}
+
+impl From<ContractState> for ContractStatusDto {
+    fn from(state: ContractState) -> Self {
+        match state {
+            ContractState::Unknown(value) => ContractStatusDto::Unknown(value),
+            known => ContractStatusDto::from(known.as_str()),
+        }
+    }
+}
+
+impl From<InvoiceStatus> for ContractState {
+    fn from(status: InvoiceStatus) -> Self {
+        match status {
+            InvoiceStatus::New => ContractState::New,
+            InvoiceStatus::InProgress => ContractState::InProgress,
+            InvoiceStatus::Completed => ContractState::Completed,
+            InvoiceStatus::Failed => ContractState::Failed,
+            InvoiceStatus::Unknown(value) => ContractState::Unknown(value),
+        }
+    }
+}
+
+impl From<SubscriptionStatus> for ContractState {
+    fn from(status: SubscriptionStatus) -> Self {
+        match status {
+            SubscriptionStatus::Active => ContractState::SubscriptionActive,
+            SubscriptionStatus::Cancelled => ContractState::SubscriptionCancelled,
+            SubscriptionStatus::Failed => ContractState::SubscriptionFailed,
+            SubscriptionStatus::Unknown(value) => ContractState::Unknown(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ContractState::*;
+
+    const KNOWN: [ContractState; 9] = [
+        New,
+        InProgress,
+        Completed,
+        Failed,
+        Cancelled,
+        SubscriptionActive,
+        SubscriptionExpired,
+        SubscriptionCancelled,
+        SubscriptionFailed,
+    ];
+
+    #[test]
+    fn status_conversions_are_lossless() {
+        for state in KNOWN.into_iter().chain([Unknown("refunded".to_string())]) {
+            let dto = ContractStatusDto::from(state.clone());
+            assert_eq!(dto.as_str(), state.as_str());
+            assert_eq!(ContractState::from(dto), state);
+        }
+        assert_eq!(ContractState::from(InvoiceStatus::InProgress), InProgress);
+        assert_eq!(
+            ContractState::from(InvoiceStatus::from("REFUNDED")),
+            Unknown("REFUNDED".to_string())
+        );
+        assert_eq!(
+            ContractState::from(SubscriptionStatus::Cancelled),
+            SubscriptionCancelled
+        );
+    }
+
+    #[test]
+    fn transitions() {
+        assert!(New.can_transition_to(&Completed));
+        assert!(InProgress.can_transition_to(&SubscriptionActive));
+        assert!(SubscriptionActive.can_transition_to(&SubscriptionFailed));
+        assert!(SubscriptionFailed.can_transition_to(&SubscriptionActive));
+        assert!(SubscriptionCancelled.can_transition_to(&SubscriptionExpired));
+        // Дубль вебхука
+        assert!(Completed.can_transition_to(&Completed));
+
+        assert!(!Completed.can_transition_to(&New));
+        assert!(!Failed.can_transition_to(&Completed));
+        assert!(!SubscriptionCancelled.can_transition_to(&SubscriptionActive));
+        assert!(!New.can_transition_to(&SubscriptionExpired));
+
+        let unknown = Unknown("refunded".to_string());
+        assert!(Completed.can_transition_to(&unknown));
+        assert!(unknown.can_transition_to(&New));
+    }
+
+    #[test]
+    fn transition_reports_invalid_pair() {
+        assert_eq!(New.transition(InProgress).unwrap(), InProgress);
+        match SubscriptionExpired.transition(SubscriptionActive) {
+            Err(LavaTopError::InvalidTransition { from, to }) => {
+                assert_eq!(from, SubscriptionExpired);
+                assert_eq!(to, SubscriptionActive);
+            }
+            other => panic!("ожидалась ошибка InvalidTransition, получено {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reachability_skips_intermediate_states_but_not_backwards() {
+        assert!(New.can_reach(&SubscriptionExpired));
+        assert!(InProgress.can_reach(&SubscriptionCancelled));
+        assert!(SubscriptionFailed.can_reach(&SubscriptionExpired));
+        assert!(!SubscriptionCancelled.can_reach(&SubscriptionActive));
+        assert!(!SubscriptionActive.can_reach(&Completed));
+        for state in KNOWN {
+            assert_eq!(state.can_reach(&New), state == New, "{state}");
+            // Прямой переход всегда означает достижимость
+            for next in state.next_states() {
+                assert!(state.can_reach(next), "{state} -> {next}");
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_states() {
+        let terminal: Vec<_> = KNOWN.iter().filter(|state| state.is_terminal()).collect();
+        assert_eq!(
+            terminal,
+            [&Completed, &Failed, &Cancelled, &SubscriptionExpired]
+        );
+        for state in terminal {
+            assert!(
+                KNOWN
+                    .iter()
+                    .all(|other| other == state || !state.can_reach(other))
+            );
+        }
+        assert!(!Unknown("refunded".to_string()).is_terminal());
+    }
+
+    #[test]
+    fn paid_and_subscription_states() {
+        let paid: Vec<_> = KNOWN.iter().filter(|state| state.is_paid()).collect();
+        assert_eq!(
+            paid,
+            [&Completed, &SubscriptionActive, &SubscriptionCancelled]
+        );
+        assert!(SubscriptionFailed.is_subscription());
+        assert!(!Completed.is_subscription());
+    }
+}
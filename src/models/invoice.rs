@@ -1,4 +1,7 @@
+use crate::error::{FieldIssue, LavaTopError, ValidationErrors};
 use crate::models::common::*;
+use crate::models::contract::ContractState;
+use crate::models::product::OfferResponse;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -33,6 +36,220 @@ pub struct InvoiceRequestDto {
     pub client_utm: Option<ClientUtmDto>,
 }
 
+/// Проверенный запрос на создание контракта.
+///
+/// Создается через [`InvoiceRequest::builder`], который отклоняет заведомо неверные
+/// параметры до обращения к API. Разыменовывается в [`InvoiceRequestDto`], поэтому
+/// передается в `create_invoice_v2` напрямую.
+///
+/// ```
+/// # use lava_top_rs::models::common::{CurrencyDto, PaymentMethod};
+/// # use lava_top_rs::models::invoice::InvoiceRequest;
+/// # fn main() -> Result<(), lava_top_rs::error::LavaTopError> {
+/// let offer_id = uuid::Uuid::new_v4();
+/// let request = InvoiceRequest::builder("buyer@example.com", offer_id)
+///     .currency(CurrencyDto::Usd)
+///     .payment_method(PaymentMethod::Stripe)
+///     .build()?;
+/// assert_eq!(request.offer_id, offer_id);
+///
+/// let error = InvoiceRequest::builder("not-an-email", uuid::Uuid::nil())
+///     .build()
+///     .unwrap_err();
+/// let fields = error.validation_errors().unwrap().fields;
+/// assert_eq!(fields.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InvoiceRequest(InvoiceRequestDto);
+
+impl InvoiceRequest {
+    /// Построитель запроса для покупателя `email` и оффера `offer_id`.
+    pub fn builder(email: impl Into<String>, offer_id: Uuid) -> InvoiceRequestBuilder {
+        InvoiceRequestBuilder {
+            request: InvoiceRequestDto {
+                email: email.into(),
+                offer_id,
+                ..Default::default()
+            },
+            offer: None,
+        }
+    }
+
+    /// Тело запроса для API.
+    pub fn into_inner(self) -> InvoiceRequestDto {
+        self.0
+    }
+}
+
+impl std::ops::Deref for InvoiceRequest {
+    type Target = InvoiceRequestDto;
+
+    fn deref(&self) -> &InvoiceRequestDto {
+        &self.0
+    }
+}
+
+impl From<InvoiceRequest> for InvoiceRequestDto {
+    fn from(request: InvoiceRequest) -> Self {
+        request.0
+    }
+}
+
+/// Построитель для [`InvoiceRequest`].
+#[derive(Debug, Clone)]
+pub struct InvoiceRequestBuilder {
+    request: InvoiceRequestDto,
+    offer: Option<OfferResponse>,
+}
+
+impl InvoiceRequestBuilder {
+    /// Валюта покупки. По умолчанию RUB.
+    pub fn currency(mut self, currency: CurrencyDto) -> Self {
+        self.request.currency = currency;
+        self
+    }
+
+    /// Способ оплаты; должен поддерживать выбранную валюту.
+    pub fn payment_method(mut self, payment_method: PaymentMethod) -> Self {
+        self.request.payment_method = Some(payment_method);
+        self
+    }
+
+    /// Периодичность оплаты (для подписок).
+    pub fn periodicity(mut self, periodicity: Periodicity) -> Self {
+        self.request.periodicity = Some(periodicity);
+        self
+    }
+
+    /// Язык покупателя для нотификаций.
+    pub fn buyer_language(mut self, language: LanguageDto) -> Self {
+        self.request.buyer_language = Some(language);
+        self
+    }
+
+    /// UTM-метки покупки.
+    pub fn client_utm(mut self, utm: ClientUtmDto) -> Self {
+        self.request.client_utm = Some(utm);
+        self
+    }
+
+    /// Оффер из каталога, с ценами которого сверяются валюта и периодичность.
+    pub fn offer(mut self, offer: &OfferResponse) -> Self {
+        self.offer = Some(offer.clone());
+        self
+    }
+
+    /// Проверяет параметры и создает запрос.
+    ///
+    /// Все найденные проблемы возвращаются вместе как [`LavaTopError::Validation`].
+    pub fn build(self) -> Result<InvoiceRequest, LavaTopError> {
+        let request = self.request;
+        let mut errors = ValidationErrors::default();
+
+        if !is_valid_email(&request.email) {
+            errors.push("email", FieldIssue::InvalidEmail(request.email.clone()));
+        }
+        if request.offer_id.is_nil() {
+            errors.push("offerId", FieldIssue::MissingOfferId);
+        }
+        if let Some(method) = &request.payment_method
+            && !supports_currency(method, &request.currency)
+        {
+            errors.push(
+                "paymentMethod",
+                FieldIssue::UnsupportedCurrency {
+                    method: method.clone(),
+                    currency: request.currency.clone(),
+                },
+            );
+        }
+        if let Some(offer) = &self.offer {
+            check_offer(&request, offer, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(InvoiceRequest(request))
+        } else {
+            Err(LavaTopError::Validation(errors))
+        }
+    }
+}
+
+/// Поддерживает ли способ оплаты валюту. Неизвестные значения не проверяются.
+fn supports_currency(method: &PaymentMethod, currency: &CurrencyDto) -> bool {
+    match currency {
+        CurrencyDto::Rub => matches!(method, PaymentMethod::Bank131 | PaymentMethod::Unknown(_)),
+        CurrencyDto::Usd | CurrencyDto::Eur => !matches!(method, PaymentMethod::Bank131),
+        CurrencyDto::Unknown(_) => true,
+    }
+}
+
+/// Сверяет валюту и периодичность запроса с ценами оффера.
+fn check_offer(request: &InvoiceRequestDto, offer: &OfferResponse, errors: &mut ValidationErrors) {
+    if offer.id != request.offer_id {
+        errors.push(
+            "offerId",
+            FieldIssue::OfferMismatch {
+                expected: request.offer_id,
+                actual: offer.id,
+            },
+        );
+        return;
+    }
+    let prices: Vec<_> = offer
+        .prices
+        .iter()
+        .filter(|price| price.currency == request.currency)
+        .collect();
+    if prices.is_empty() {
+        errors.push(
+            "currency",
+            FieldIssue::NoPriceInCurrency(request.currency.clone()),
+        );
+        return;
+    }
+    match &request.periodicity {
+        Some(periodicity) => {
+            if !prices
+                .iter()
+                .any(|price| price.periodicity_or_one_time() == *periodicity)
+            {
+                errors.push(
+                    "periodicity",
+                    FieldIssue::NoPriceWithPeriodicity(periodicity.clone()),
+                );
+            }
+        }
+        None => {
+            if !prices
+                .iter()
+                .any(|price| price.periodicity_or_one_time() == Periodicity::OneTime)
+            {
+                errors.push("periodicity", FieldIssue::SubscriptionOnly);
+            }
+        }
+    }
+}
+
+/// Упрощенная проверка синтаксиса адреса почты: `локальная часть@домен.зона` без пробелов.
+fn is_valid_email(email: &str) -> bool {
+    if email.len() > 254 || email.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local.len() <= 64
+        && !local.contains('@')
+        && domain.contains('.')
+        && domain
+            .split('.')
+            .all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'))
+}
+
 /// Ответ при создании счета (v1 и v2). Содержит ссылку на оплату.
 #[derive(Deserialize, Debug, Clone)]
 pub struct InvoicePaymentParamsResponse {
@@ -107,6 +324,16 @@ pub struct InvoiceResponseV2 {
     pub client_utm: Option<ClientUtmDto>,
 }
 
+impl InvoiceResponseV2 {
+    /// Состояние контракта; для подписок учитывается `subscription_status`.
+    pub fn state(&self) -> ContractState {
+        match &self.subscription_status {
+            Some(status) => status.clone().into(),
+            None => self.status.clone().into(),
+        }
+    }
+}
+
 /// Структура для пагинированного ответа списка контрактов.
 #[derive(Deserialize, Debug, Clone)]
 pub struct InvoicePageResponse {
@@ -136,18 +363,199 @@ pub struct ListInvoicesParams {
     pub end_date: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub buyer_email: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "opt_vec_as_comma_separated",
+        default
+    )]
     pub currencies: Option<Vec<CurrencyDto>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last4_card_digits: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub product_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "opt_vec_as_comma_separated",
+        default
+    )]
     pub invoice_types: Option<Vec<InvoiceType>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "opt_vec_as_comma_separated",
+        default
+    )]
     pub invoice_statuses: Option<Vec<InvoiceStatus>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<i64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BUYER: &str = "buyer@example.com";
+
+    fn issues(result: Result<InvoiceRequest, LavaTopError>) -> Vec<(String, FieldIssue)> {
+        result
+            .unwrap_err()
+            .validation_errors()
+            .unwrap()
+            .fields
+            .into_iter()
+            .map(|field| (field.field, field.issue.unwrap()))
+            .collect()
+    }
+
+    fn offer(id: Uuid, prices: serde_json::Value) -> OfferResponse {
+        serde_json::from_value(json!({ "id": id, "name": "Базовый", "prices": prices })).unwrap()
+    }
+
+    #[test]
+    fn email_syntax() {
+        for email in [
+            BUYER,
+            "first.last+tag@sub.example.co",
+            "\"quoted\"@example.com",
+        ] {
+            assert!(is_valid_email(email), "{email}");
+        }
+        for email in [
+            "",
+            "buyer",
+            "@example.com",
+            "buyer@",
+            "buyer@localhost",
+            "buyer@example..com",
+            "buyer@-example.com",
+            "buyer@example-.com",
+            "buy er@example.com",
+            "a@b@example.com",
+            &format!("{}@example.com", "a".repeat(65)),
+            &format!("buyer@{}.com", "a".repeat(250)),
+        ] {
+            assert!(!is_valid_email(email), "{email}");
+        }
+    }
+
+    #[test]
+    fn collects_all_issues() {
+        let issues = issues(
+            InvoiceRequest::builder("not-an-email", Uuid::nil())
+                .payment_method(PaymentMethod::Stripe)
+                .build(),
+        );
+        assert_eq!(
+            issues,
+            [
+                (
+                    "email".to_string(),
+                    FieldIssue::InvalidEmail("not-an-email".to_string())
+                ),
+                ("offerId".to_string(), FieldIssue::MissingOfferId),
+                (
+                    "paymentMethod".to_string(),
+                    FieldIssue::UnsupportedCurrency {
+                        method: PaymentMethod::Stripe,
+                        currency: CurrencyDto::Rub,
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn payment_method_currencies() {
+        let offer_id = Uuid::new_v4();
+        let build = |method: PaymentMethod, currency: CurrencyDto| {
+            InvoiceRequest::builder(BUYER, offer_id)
+                .currency(currency)
+                .payment_method(method)
+                .build()
+        };
+        assert!(build(PaymentMethod::Bank131, CurrencyDto::Rub).is_ok());
+        assert!(build(PaymentMethod::Bank131, CurrencyDto::Usd).is_err());
+        assert!(build(PaymentMethod::Unlimint, CurrencyDto::Rub).is_err());
+        assert!(build(PaymentMethod::Paypal, CurrencyDto::Eur).is_ok());
+        assert!(build(PaymentMethod::Stripe, CurrencyDto::Usd).is_ok());
+        assert!(build(PaymentMethod::from("CRYPTO"), CurrencyDto::Rub).is_ok());
+        assert!(build(PaymentMethod::Stripe, CurrencyDto::from("KZT")).is_ok());
+    }
+
+    #[test]
+    fn checks_offer_prices() {
+        let offer_id = Uuid::new_v4();
+        let subscription = offer(
+            offer_id,
+            json!([
+                { "amount": 990.0, "currency": "RUB", "periodicity": "MONTHLY" },
+                { "amount": 10.0, "currency": "USD", "periodicity": "MONTHLY" },
+            ]),
+        );
+        let builder = || InvoiceRequest::builder(BUYER, offer_id).offer(&subscription);
+
+        assert!(builder().periodicity(Periodicity::Monthly).build().is_ok());
+        assert_eq!(
+            issues(builder().build()),
+            [("periodicity".to_string(), FieldIssue::SubscriptionOnly)]
+        );
+        assert_eq!(
+            issues(builder().periodicity(Periodicity::PeriodYear).build()),
+            [(
+                "periodicity".to_string(),
+                FieldIssue::NoPriceWithPeriodicity(Periodicity::PeriodYear)
+            )]
+        );
+        assert_eq!(
+            issues(builder().currency(CurrencyDto::Eur).build()),
+            [(
+                "currency".to_string(),
+                FieldIssue::NoPriceInCurrency(CurrencyDto::Eur)
+            )]
+        );
+
+        // Цена без периодичности считается разовой
+        let one_time = offer(offer_id, json!([{ "amount": 990.0, "currency": "RUB" }]));
+        let request = InvoiceRequest::builder(BUYER, offer_id)
+            .offer(&one_time)
+            .build()
+            .unwrap();
+        assert_eq!(request.offer_id, offer_id);
+
+        let other = Uuid::new_v4();
+        assert_eq!(
+            issues(
+                InvoiceRequest::builder(BUYER, other)
+                    .offer(&one_time)
+                    .build()
+            ),
+            [(
+                "offerId".to_string(),
+                FieldIssue::OfferMismatch {
+                    expected: other,
+                    actual: offer_id,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn builds_dto() {
+        let offer_id = Uuid::new_v4();
+        let dto: InvoiceRequestDto = InvoiceRequest::builder(BUYER, offer_id)
+            .currency(CurrencyDto::Usd)
+            .payment_method(PaymentMethod::Stripe)
+            .periodicity(Periodicity::Monthly)
+            .buyer_language(LanguageDto::En)
+            .build()
+            .unwrap()
+            .into();
+        let json = serde_json::to_value(&dto).unwrap();
+        assert_eq!(json["email"], BUYER);
+        assert_eq!(json["currency"], "USD");
+        assert_eq!(json["paymentMethod"], "STRIPE");
+        assert_eq!(json["periodicity"], "MONTHLY");
+    }
+}
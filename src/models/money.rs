@@ -0,0 +1,442 @@
+use crate::error::{Locale, Localized};
+use crate::models::common::{AmountTotalDto, CurrencyDto, PriceDto};
+use crate::models::invoice::InvoiceReceiptResponse;
+use crate::models::product::UpdatePriceRequest;
+use crate::models::report::PartnerSaleDto;
+use crate::models::webhook::{PaymentEvent, PurchaseWebhookLog};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Ошибки операций с [`Money`].
+#[derive(Error, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MoneyError {
+    /// Операция над суммами в разных валютах.
+    CurrencyMismatch {
+        left: CurrencyDto,
+        right: CurrencyDto,
+    },
+    /// Результат не помещается в Decimal.
+    Overflow,
+    /// Сумма из API не может быть представлена точно (NaN, бесконечность, слишком большое число).
+    InvalidAmount(f64),
+    /// Сумма содержит больше знаков после запятой, чем допускает валюта.
+    SubMinorUnit {
+        amount: Decimal,
+        currency: CurrencyDto,
+    },
+}
+
+impl MoneyError {
+    /// Стабильный машиночитаемый код ошибки, не зависящий от языка.
+    pub fn code(&self) -> &'static str {
+        match self {
+            MoneyError::CurrencyMismatch { .. } => "money.currency_mismatch",
+            MoneyError::Overflow => "money.overflow",
+            MoneyError::InvalidAmount(_) => "money.invalid_amount",
+            MoneyError::SubMinorUnit { .. } => "money.sub_minor_unit",
+        }
+    }
+
+    /// Сообщение об ошибке на языке `locale`.
+    pub fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            error: self,
+            locale,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let ru = locale == Locale::Ru;
+        match self {
+            MoneyError::CurrencyMismatch { left, right } if ru => {
+                write!(f, "Нельзя смешивать валюты: {left} и {right}")
+            }
+            MoneyError::CurrencyMismatch { left, right } => {
+                write!(f, "Cannot mix currencies: {left} and {right}")
+            }
+            MoneyError::Overflow if ru => f.write_str("Переполнение при вычислении суммы"),
+            MoneyError::Overflow => f.write_str("Amount overflow"),
+            MoneyError::InvalidAmount(amount) if ru => write!(f, "Неверная сумма: {amount}"),
+            MoneyError::InvalidAmount(amount) => write!(f, "Invalid amount: {amount}"),
+            MoneyError::SubMinorUnit { amount, currency } if ru => {
+                write!(
+                    f,
+                    "Сумма {amount} точнее минимальной единицы валюты {currency}"
+                )
+            }
+            MoneyError::SubMinorUnit { amount, currency } => {
+                write!(
+                    f,
+                    "Amount {amount} is finer than the minor unit of {currency}"
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Locale::default())
+    }
+}
+
+impl fmt::Display for Localized<'_, MoneyError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write(f, self.locale)
+    }
+}
+
+/// Точная денежная сумма в конкретной валюте.
+///
+/// Сериализуется в формат API: `{"amount": 40.5, "currency": "RUB"}`.
+/// Арифметика не смешивает валюты и не паникует при переполнении.
+///
+/// ```
+/// # use lava_top_rs::models::common::CurrencyDto;
+/// # use lava_top_rs::models::money::Money;
+/// let a = Money::from_minor_units(1050, CurrencyDto::Rub);
+/// let b = Money::from_f64(0.1, CurrencyDto::Rub).unwrap();
+/// assert_eq!(a.checked_add(&b).unwrap().to_string(), "10.60 RUB");
+/// assert!(a.checked_add(&Money::zero(CurrencyDto::Usd)).is_err());
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Money {
+    #[serde(with = "rust_decimal::serde::float")]
+    amount: Decimal,
+    currency: CurrencyDto,
+}
+
+impl Money {
+    /// Создает сумму из точного десятичного значения.
+    pub fn new(amount: Decimal, currency: CurrencyDto) -> Self {
+        Self { amount, currency }
+    }
+
+    /// Нулевая сумма в валюте.
+    pub fn zero(currency: CurrencyDto) -> Self {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    /// Создает сумму из количества минимальных единиц валюты (копеек, центов).
+    pub fn from_minor_units(units: i64, currency: CurrencyDto) -> Self {
+        Self::new(Decimal::new(units, currency.minor_units()), currency)
+    }
+
+    /// Создает сумму из числа, полученного от API.
+    ///
+    /// Используется кратчайшее десятичное представление числа, поэтому `0.1` превращается
+    /// ровно в `0.1`, а не в ближайшее к нему двоичное значение.
+    pub fn from_f64(amount: f64, currency: CurrencyDto) -> Result<Self, MoneyError> {
+        if !amount.is_finite() {
+            return Err(MoneyError::InvalidAmount(amount));
+        }
+        let amount = Decimal::from_str(&amount.to_string())
+            .map_err(|_| MoneyError::InvalidAmount(amount))?;
+        Ok(Self::new(amount, currency))
+    }
+
+    /// Десятичное значение суммы.
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    /// Валюта суммы.
+    pub fn currency(&self) -> &CurrencyDto {
+        &self.currency
+    }
+
+    /// Сумма в минимальных единицах валюты. Ошибка, если сумма точнее минимальной единицы.
+    pub fn to_minor_units(&self) -> Result<i64, MoneyError> {
+        let scale = Decimal::from(10_i64.pow(self.currency.minor_units()));
+        let units = self.amount.checked_mul(scale).ok_or(MoneyError::Overflow)?;
+        if !units.fract().is_zero() {
+            return Err(MoneyError::SubMinorUnit {
+                amount: self.amount,
+                currency: self.currency.clone(),
+            });
+        }
+        i64::try_from(units).map_err(|_| MoneyError::Overflow)
+    }
+
+    /// Округляет сумму до минимальной единицы валюты (половина — от нуля).
+    pub fn round_to_minor_units(&self) -> Self {
+        let amount = self.amount.round_dp_with_strategy(
+            self.currency.minor_units(),
+            RoundingStrategy::MidpointAwayFromZero,
+        );
+        Self::new(amount, self.currency.clone())
+    }
+
+    /// Сложение сумм в одной валюте.
+    pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.ensure_same_currency(other)?;
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self::new(amount, self.currency.clone()))
+    }
+
+    /// Вычитание сумм в одной валюте.
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, MoneyError> {
+        self.ensure_same_currency(other)?;
+        let amount = self
+            .amount
+            .checked_sub(other.amount)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self::new(amount, self.currency.clone()))
+    }
+
+    /// Умножение суммы на безразмерный коэффициент (количество, ставка комиссии).
+    pub fn checked_mul(&self, factor: Decimal) -> Result<Money, MoneyError> {
+        let amount = self
+            .amount
+            .checked_mul(factor)
+            .ok_or(MoneyError::Overflow)?;
+        Ok(Self::new(amount, self.currency.clone()))
+    }
+
+    /// Сумма последовательности в заданной валюте. Пустая последовательность дает ноль.
+    pub fn sum<'a>(
+        currency: CurrencyDto,
+        items: impl IntoIterator<Item = &'a Money>,
+    ) -> Result<Money, MoneyError> {
+        items
+            .into_iter()
+            .try_fold(Self::zero(currency), |acc, item| acc.checked_add(item))
+    }
+
+    /// Нулевая ли сумма.
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    /// Отрицательная ли сумма.
+    pub fn is_negative(&self) -> bool {
+        self.amount.is_sign_negative() && !self.amount.is_zero()
+    }
+
+    fn ensure_same_currency(&self, other: &Money) -> Result<(), MoneyError> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(MoneyError::CurrencyMismatch {
+                left: self.currency.clone(),
+                right: other.currency.clone(),
+            })
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut amount = self.amount;
+        if amount.scale() < self.currency.minor_units() {
+            amount.rescale(self.currency.minor_units());
+        }
+        write!(f, "{amount} {}", self.currency)
+    }
+}
+
+// --- Доступ к суммам моделей API ---
+
+impl PriceDto {
+    /// Цена как [`Money`] (если цена указана).
+    pub fn money(&self) -> Result<Option<Money>, MoneyError> {
+        self.amount
+            .map(|amount| Money::from_f64(amount, self.currency.clone()))
+            .transpose()
+    }
+}
+
+impl AmountTotalDto {
+    /// Сумма как [`Money`].
+    pub fn money(&self) -> Result<Money, MoneyError> {
+        Money::from_f64(self.amount, self.currency.clone())
+    }
+}
+
+impl InvoiceReceiptResponse {
+    /// Сумма чека как [`Money`].
+    pub fn money(&self) -> Result<Money, MoneyError> {
+        Money::from_f64(self.amount, self.currency.clone())
+    }
+
+    /// Комиссия как [`Money`] (если указана).
+    pub fn fee_money(&self) -> Result<Option<Money>, MoneyError> {
+        self.fee
+            .map(|fee| Money::from_f64(fee, self.currency.clone()))
+            .transpose()
+    }
+}
+
+impl PartnerSaleDto {
+    /// Общая сумма продаж как [`Money`].
+    pub fn money(&self) -> Result<Money, MoneyError> {
+        Money::from_f64(self.amount_total, self.currency.clone())
+    }
+}
+
+impl PurchaseWebhookLog {
+    /// Сумма операции как [`Money`] (если указаны сумма и валюта).
+    pub fn money(&self) -> Result<Option<Money>, MoneyError> {
+        match (self.amount, &self.currency) {
+            (Some(amount), Some(currency)) => Money::from_f64(amount, currency.clone()).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl PaymentEvent {
+    /// Сумма платежа как [`Money`].
+    pub fn money(&self) -> Result<Money, MoneyError> {
+        Money::from_f64(self.amount, self.currency.clone())
+    }
+}
+
+impl TryFrom<&Money> for UpdatePriceRequest {
+    type Error = MoneyError;
+
+    fn try_from(money: &Money) -> Result<Self, Self::Error> {
+        use rust_decimal::prelude::ToPrimitive;
+        let amount = money.amount.to_f64().ok_or(MoneyError::Overflow)?;
+        Ok(Self {
+            amount,
+            currency: money.currency.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rub(amount: &str) -> Money {
+        Money::new(Decimal::from_str(amount).unwrap(), CurrencyDto::Rub)
+    }
+
+    #[test]
+    fn from_f64_uses_shortest_representation() {
+        assert_eq!(Money::from_f64(0.1, CurrencyDto::Rub).unwrap(), rub("0.1"));
+        assert_eq!(
+            Money::from_f64(990.0, CurrencyDto::Rub)
+                .unwrap()
+                .to_string(),
+            "990.00 RUB"
+        );
+        for amount in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300] {
+            assert!(matches!(
+                Money::from_f64(amount, CurrencyDto::Rub),
+                Err(MoneyError::InvalidAmount(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn minor_units_round_trip() {
+        let money = Money::from_minor_units(1050, CurrencyDto::Usd);
+        assert_eq!(money.to_string(), "10.50 USD");
+        assert_eq!(money.to_minor_units(), Ok(1050));
+        assert_eq!(
+            Money::from_minor_units(-5, CurrencyDto::Eur).to_minor_units(),
+            Ok(-5)
+        );
+        assert_eq!(
+            rub("0.001").to_minor_units(),
+            Err(MoneyError::SubMinorUnit {
+                amount: Decimal::from_str("0.001").unwrap(),
+                currency: CurrencyDto::Rub,
+            })
+        );
+        assert_eq!(
+            Money::new(Decimal::MAX, CurrencyDto::Rub).to_minor_units(),
+            Err(MoneyError::Overflow)
+        );
+    }
+
+    #[test]
+    fn rounds_half_away_from_zero() {
+        assert_eq!(rub("10.005").round_to_minor_units(), rub("10.01"));
+        assert_eq!(rub("10.004").round_to_minor_units(), rub("10.00"));
+        assert_eq!(rub("-10.005").round_to_minor_units(), rub("-10.01"));
+        assert_eq!(rub("10.5").round_to_minor_units(), rub("10.5"));
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        let sum = rub("0.1").checked_add(&rub("0.2")).unwrap();
+        assert_eq!(sum, rub("0.3"));
+        assert_eq!(
+            sum.checked_sub(&rub("0.3")).unwrap().to_string(),
+            "0.00 RUB"
+        );
+        let fee = rub("990")
+            .checked_mul(Decimal::from_str("0.035").unwrap())
+            .unwrap();
+        assert_eq!(fee.round_to_minor_units().to_string(), "34.65 RUB");
+        assert!(rub("1").checked_sub(&rub("2")).unwrap().is_negative());
+        assert!(!rub("-0").is_negative());
+        assert!(rub("-0").is_zero());
+    }
+
+    #[test]
+    fn arithmetic_rejects_mixed_currencies_and_overflow() {
+        let usd = Money::from_minor_units(100, CurrencyDto::Usd);
+        assert_eq!(
+            rub("1").checked_add(&usd),
+            Err(MoneyError::CurrencyMismatch {
+                left: CurrencyDto::Rub,
+                right: CurrencyDto::Usd,
+            })
+        );
+        assert!(matches!(
+            rub("1").checked_sub(&usd),
+            Err(MoneyError::CurrencyMismatch { .. })
+        ));
+        let max = Money::new(Decimal::MAX, CurrencyDto::Rub);
+        assert_eq!(max.checked_add(&max), Err(MoneyError::Overflow));
+        assert_eq!(max.checked_mul(Decimal::TWO), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn sum_in_currency() {
+        let items = [rub("1.10"), rub("2.20"), rub("3.30")];
+        assert_eq!(Money::sum(CurrencyDto::Rub, &items).unwrap(), rub("6.60"));
+        assert_eq!(
+            Money::sum(CurrencyDto::Usd, []).unwrap(),
+            Money::zero(CurrencyDto::Usd)
+        );
+        assert!(matches!(
+            Money::sum(CurrencyDto::Usd, &items),
+            Err(MoneyError::CurrencyMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn serde_uses_api_format() {
+        let money = rub("40.5");
+        let json = serde_json::to_value(&money).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "amount": 40.5, "currency": "RUB" })
+        );
+        assert_eq!(serde_json::from_value::<Money>(json).unwrap(), money);
+    }
+
+    #[test]
+    fn error_codes_and_locales() {
+        let error = MoneyError::CurrencyMismatch {
+            left: CurrencyDto::Rub,
+            right: CurrencyDto::Usd,
+        };
+        assert_eq!(error.code(), "money.currency_mismatch");
+        assert_ne!(
+            error.localized(Locale::En).to_string(),
+            error.localized(Locale::Ru).to_string()
+        );
+    }
+}
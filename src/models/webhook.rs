@@ -1,17 +1,19 @@
+use crate::error::LavaTopError;
 use crate::models::common::*;
+use crate::models::contract::ContractState;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use uuid::Uuid;
 
-/// Тип события вебхука.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
-#[serde(rename_all = "snake_case")]
-pub enum WebhookEventType {
-    PaymentSuccess,
-    PaymentFailed,
-    SubscriptionRecurringPaymentSuccess,
-    SubscriptionRecurringPaymentFailed,
-    SubscriptionCancelled,
+api_enum! {
+    /// Тип события вебхука.
+    pub enum WebhookEventType {
+        PaymentSuccess = "payment_success",
+        PaymentFailed = "payment_failed",
+        SubscriptionRecurringPaymentSuccess = "subscription_recurring_payment_success",
+        SubscriptionRecurringPaymentFailed = "subscription_recurring_payment_failed",
+        SubscriptionCancelled = "subscription_cancelled",
+    }
 }
 
 /// Информация о продукте в теле вебхука.
@@ -63,3 +65,285 @@ pub struct PurchaseWebhookLog {
     #[serde(rename = "willExpireAt")]
     pub will_expire_at: Option<DateTime<Utc>>,
 }
+
+// --- Типизированные события ---
+
+/// Платеж по продукту (события `payment_success` и `payment_failed`).
+#[derive(Debug, Clone)]
+pub struct PaymentEvent {
+    /// Идентификатор контракта.
+    pub contract_id: Uuid,
+    pub product: WebhookProduct,
+    pub buyer: WebhookBuyer,
+    /// Сумма операции.
+    pub amount: f64,
+    pub currency: CurrencyDto,
+    pub status: ContractStatusDto,
+    pub timestamp: DateTime<Utc>,
+    /// UTM-метки, связанные с покупкой.
+    pub client_utm: Option<ClientUtmDto>,
+    /// Сообщение об ошибке (обычно заполнено для неуспешных платежей).
+    pub error_message: Option<String>,
+}
+
+impl PaymentEvent {
+    /// Состояние контракта в общей модели.
+    pub fn state(&self) -> ContractState {
+        self.status.clone().into()
+    }
+}
+
+/// Очередное списание по подписке (события `subscription_recurring_payment_*`).
+#[derive(Debug, Clone)]
+pub struct RecurringPaymentEvent {
+    /// Идентификатор родительского контракта (первой покупки подписки).
+    pub parent_contract_id: Uuid,
+    /// Данные платежа; `contract_id` — идентификатор контракта списания.
+    pub payment: PaymentEvent,
+}
+
+/// Отмена подписки (событие `subscription_cancelled`).
+#[derive(Debug, Clone)]
+pub struct SubscriptionCancelledEvent {
+    /// Идентификатор контракта подписки.
+    pub contract_id: Uuid,
+    pub parent_contract_id: Option<Uuid>,
+    pub product: Option<WebhookProduct>,
+    pub buyer: Option<WebhookBuyer>,
+    /// Дата отмены подписки.
+    pub cancelled_at: DateTime<Utc>,
+    /// Дата, до которой подписка остается активной.
+    pub will_expire_at: DateTime<Utc>,
+    pub client_utm: Option<ClientUtmDto>,
+}
+
+/// Событие вебхука с полями, гарантированными для его типа.
+///
+/// Десериализуется из того же JSON, что и [`PurchaseWebhookLog`]; если для типа события
+/// отсутствует обязательное поле, возвращается ошибка.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "PurchaseWebhookLog")]
+pub enum WebhookEvent {
+    PaymentSuccess(PaymentEvent),
+    PaymentFailed(PaymentEvent),
+    SubscriptionRecurringPaymentSuccess(RecurringPaymentEvent),
+    SubscriptionRecurringPaymentFailed(RecurringPaymentEvent),
+    SubscriptionCancelled(SubscriptionCancelledEvent),
+    /// Событие неизвестного этой версии библиотеки типа (исходное тело без проверок).
+    Unknown(PurchaseWebhookLog),
+}
+
+impl WebhookEvent {
+    /// Тип события.
+    pub fn event_type(&self) -> WebhookEventType {
+        match self {
+            Self::PaymentSuccess(_) => WebhookEventType::PaymentSuccess,
+            Self::PaymentFailed(_) => WebhookEventType::PaymentFailed,
+            Self::SubscriptionRecurringPaymentSuccess(_) => {
+                WebhookEventType::SubscriptionRecurringPaymentSuccess
+            }
+            Self::SubscriptionRecurringPaymentFailed(_) => {
+                WebhookEventType::SubscriptionRecurringPaymentFailed
+            }
+            Self::SubscriptionCancelled(_) => WebhookEventType::SubscriptionCancelled,
+            Self::Unknown(log) => log.event_type.clone(),
+        }
+    }
+
+    /// Идентификатор контракта, к которому относится событие.
+    pub fn contract_id(&self) -> Uuid {
+        match self {
+            Self::PaymentSuccess(e) | Self::PaymentFailed(e) => e.contract_id,
+            Self::SubscriptionRecurringPaymentSuccess(e)
+            | Self::SubscriptionRecurringPaymentFailed(e) => e.payment.contract_id,
+            Self::SubscriptionCancelled(e) => e.contract_id,
+            Self::Unknown(log) => log.contract_id,
+        }
+    }
+}
+
+impl TryFrom<PurchaseWebhookLog> for WebhookEvent {
+    type Error = LavaTopError;
+
+    fn try_from(log: PurchaseWebhookLog) -> Result<Self, Self::Error> {
+        let event_type = log.event_type.clone();
+        let event = match event_type {
+            WebhookEventType::PaymentSuccess => Self::PaymentSuccess(payment_event(log)?),
+            WebhookEventType::PaymentFailed => Self::PaymentFailed(payment_event(log)?),
+            WebhookEventType::SubscriptionRecurringPaymentSuccess => {
+                Self::SubscriptionRecurringPaymentSuccess(recurring_payment_event(log)?)
+            }
+            WebhookEventType::SubscriptionRecurringPaymentFailed => {
+                Self::SubscriptionRecurringPaymentFailed(recurring_payment_event(log)?)
+            }
+            WebhookEventType::SubscriptionCancelled => {
+                Self::SubscriptionCancelled(SubscriptionCancelledEvent {
+                    contract_id: log.contract_id,
+                    parent_contract_id: log.parent_contract_id,
+                    product: log.product,
+                    buyer: log.buyer,
+                    cancelled_at: required(log.cancelled_at, &event_type, "cancelledAt")?,
+                    will_expire_at: required(log.will_expire_at, &event_type, "willExpireAt")?,
+                    client_utm: log.client_utm,
+                })
+            }
+            WebhookEventType::Unknown(_) => Self::Unknown(log),
+        };
+        Ok(event)
+    }
+}
+
+fn required<T>(
+    value: Option<T>,
+    event_type: &WebhookEventType,
+    field: &str,
+) -> Result<T, LavaTopError> {
+    value.ok_or_else(|| LavaTopError::MissingField(format!("{field} ({event_type})")))
+}
+
+fn payment_event(log: PurchaseWebhookLog) -> Result<PaymentEvent, LavaTopError> {
+    let event_type = &log.event_type;
+    Ok(PaymentEvent {
+        contract_id: log.contract_id,
+        product: required(log.product, event_type, "product")?,
+        buyer: required(log.buyer, event_type, "buyer")?,
+        amount: required(log.amount, event_type, "amount")?,
+        currency: required(log.currency, event_type, "currency")?,
+        status: required(log.status, event_type, "status")?,
+        timestamp: required(log.timestamp, event_type, "timestamp")?,
+        client_utm: log.client_utm,
+        error_message: log.error_message,
+    })
+}
+
+fn recurring_payment_event(log: PurchaseWebhookLog) -> Result<RecurringPaymentEvent, LavaTopError> {
+    let parent_contract_id = required(log.parent_contract_id, &log.event_type, "parentContractId")?;
+    Ok(RecurringPaymentEvent {
+        parent_contract_id,
+        payment: payment_event(log)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    const CONTRACT: &str = "7ea82675-4ded-4133-95a7-a6efbaf165cc";
+    const PARENT: &str = "d31384b8-e412-4be5-a2ec-297ae6666c8f";
+
+    fn payment(event_type: &str) -> Value {
+        json!({
+            "eventType": event_type,
+            "product": { "id": "72d53efb-3696-469f-b856-f0d815748dd6", "title": "Курс" },
+            "contractId": CONTRACT,
+            "buyer": { "email": "buyer@example.com" },
+            "amount": 990.0,
+            "currency": "RUB",
+            "status": "completed",
+            "timestamp": "2024-02-05T09:38:27.33Z",
+        })
+    }
+
+    fn event(value: Value) -> Result<WebhookEvent, LavaTopError> {
+        let log: PurchaseWebhookLog = serde_json::from_value(value).unwrap();
+        WebhookEvent::try_from(log)
+    }
+
+    #[test]
+    fn payment_success() {
+        let WebhookEvent::PaymentSuccess(payment) = event(payment("payment_success")).unwrap()
+        else {
+            panic!("ожидалось событие payment_success");
+        };
+        assert_eq!(payment.contract_id.to_string(), CONTRACT);
+        assert_eq!(payment.amount, 990.0);
+        assert_eq!(payment.currency, CurrencyDto::Rub);
+        assert_eq!(payment.status, ContractStatusDto::Completed);
+    }
+
+    #[test]
+    fn payment_without_required_field() {
+        for field in [
+            "product",
+            "buyer",
+            "amount",
+            "currency",
+            "status",
+            "timestamp",
+        ] {
+            let mut value = payment("payment_failed");
+            value.as_object_mut().unwrap().remove(field);
+            match event(value) {
+                Err(LavaTopError::MissingField(message)) => {
+                    assert_eq!(message, format!("{field} (payment_failed)"));
+                }
+                other => panic!("{field}: ожидалась ошибка MissingField, получено {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn recurring_payment_requires_parent() {
+        let mut value = payment("subscription_recurring_payment_success");
+        assert!(matches!(
+            event(value.clone()),
+            Err(LavaTopError::MissingField(message)) if message.starts_with("parentContractId")
+        ));
+
+        value["parentContractId"] = json!(PARENT);
+        let event = event(value).unwrap();
+        assert_eq!(
+            event.event_type(),
+            WebhookEventType::SubscriptionRecurringPaymentSuccess
+        );
+        let WebhookEvent::SubscriptionRecurringPaymentSuccess(recurring) = event else {
+            unreachable!();
+        };
+        assert_eq!(recurring.parent_contract_id.to_string(), PARENT);
+        assert_eq!(recurring.payment.contract_id.to_string(), CONTRACT);
+    }
+
+    #[test]
+    fn subscription_cancelled() {
+        let mut value = json!({
+            "eventType": "subscription_cancelled",
+            "contractId": CONTRACT,
+            "cancelledAt": "2024-02-05T09:38:27.33Z",
+        });
+        assert!(matches!(
+            event(value.clone()),
+            Err(LavaTopError::MissingField(message)) if message.starts_with("willExpireAt")
+        ));
+
+        value["willExpireAt"] = json!("2024-03-05T09:38:27.33Z");
+        let WebhookEvent::SubscriptionCancelled(cancelled) = event(value).unwrap() else {
+            panic!("ожидалось событие subscription_cancelled");
+        };
+        assert_eq!(cancelled.contract_id.to_string(), CONTRACT);
+        assert!(cancelled.product.is_none());
+        assert!(cancelled.will_expire_at > cancelled.cancelled_at);
+    }
+
+    #[test]
+    fn unknown_event_type_keeps_raw_log() {
+        let mut log: PurchaseWebhookLog =
+            serde_json::from_value(payment("payment_success")).unwrap();
+        log.event_type = WebhookEventType::from("payment_refunded");
+        log.amount = None;
+        let event = WebhookEvent::try_from(log).unwrap();
+        assert!(matches!(&event, WebhookEvent::Unknown(log) if log.amount.is_none()));
+        assert_eq!(event.event_type().as_str(), "payment_refunded");
+        assert_eq!(event.contract_id().to_string(), CONTRACT);
+    }
+
+    #[test]
+    fn deserializes_directly() {
+        let event: WebhookEvent = serde_json::from_value(payment("payment_success")).unwrap();
+        assert!(matches!(event, WebhookEvent::PaymentSuccess(_)));
+
+        let mut value = payment("payment_success");
+        value.as_object_mut().unwrap().remove("buyer");
+        assert!(serde_json::from_value::<WebhookEvent>(value).is_err());
+    }
+}
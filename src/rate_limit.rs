@@ -0,0 +1,476 @@
+use crate::error::BoxError;
+use crate::retry::parse_retry_after;
+use crate::transport::{HttpRequest, HttpResponse};
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service, ServiceExt};
+
+/// Максимальная пауза по `Retry-After` по умолчанию, как у [`RetryPolicy`](crate::retry::RetryPolicy).
+const DEFAULT_MAX_PAUSE: Duration = Duration::from_secs(60);
+
+/// Группа эндпоинтов с общей квотой запросов.
+///
+/// Клиент помечает ею каждый запрос через расширения `http::Request`; запросы без метки
+/// (например, отправленные в [`RateLimit`] напрямую) относятся к [`EndpointGroup::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointGroup {
+    /// Создание и получение контрактов (`/api/*/invoice*`).
+    Invoices,
+    /// Отчеты о продажах (`/api/v1/sales`).
+    Sales,
+    /// Продукты и лента (`/api/v2/products`, `/api/v1/feed`).
+    Products,
+    /// Остальные эндпоинты (подписки, донаты).
+    Other,
+}
+
+impl EndpointGroup {
+    const ALL: [EndpointGroup; 4] = [
+        EndpointGroup::Invoices,
+        EndpointGroup::Sales,
+        EndpointGroup::Products,
+        EndpointGroup::Other,
+    ];
+
+    /// Группа эндпоинта по его пути относительно базового URL (`/api/v1/invoices/...`).
+    ///
+    /// Сравнивается только сегмент ресурса, поэтому префикс базового URL и query не влияют
+    /// на выбор группы.
+    pub(crate) fn for_endpoint(endpoint: &str) -> Self {
+        let mut segments = endpoint.split('/').filter(|segment| !segment.is_empty());
+        let resource = match (segments.next(), segments.next(), segments.next()) {
+            (Some("api"), Some(_version), Some(resource)) => resource,
+            _ => return EndpointGroup::Other,
+        };
+        match resource {
+            "invoice" | "invoices" => EndpointGroup::Invoices,
+            "sales" => EndpointGroup::Sales,
+            "products" | "feed" => EndpointGroup::Products,
+            _ => EndpointGroup::Other,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Квота запросов: средняя частота и допустимый всплеск.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    interval: Duration,
+    burst: u32,
+}
+
+impl Quota {
+    /// `requests` запросов в секунду; всплеск по умолчанию равен `requests`.
+    pub fn per_second(requests: u32) -> Self {
+        Self::per(requests, Duration::from_secs(1))
+    }
+
+    /// `requests` запросов в минуту; всплеск по умолчанию равен `requests`.
+    pub fn per_minute(requests: u32) -> Self {
+        Self::per(requests, Duration::from_secs(60))
+    }
+
+    /// `requests` запросов за период `period`; всплеск по умолчанию равен `requests`.
+    pub fn per(requests: u32, period: Duration) -> Self {
+        let requests = requests.max(1);
+        Self {
+            interval: period / requests,
+            burst: requests,
+        }
+    }
+
+    /// Сколько запросов можно отправить подряд без ожидания.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    quota: Option<Quota>,
+    /// Теоретическое время следующего запроса (алгоритм GCRA).
+    next_at: Option<Instant>,
+    /// До какого момента сервер попросил не отправлять запросы (`Retry-After` на 429).
+    paused_until: Option<Instant>,
+}
+
+impl Bucket {
+    /// Резервирует место в очереди и возвращает момент, когда запрос можно отправить.
+    fn reserve(&mut self, now: Instant) -> Instant {
+        let mut ready_at = now;
+        if let Some(quota) = self.quota {
+            let tolerance = quota.interval * (quota.burst - 1);
+            let next_at = self.next_at.map_or(now, |at| at.max(now));
+            ready_at = next_at.checked_sub(tolerance).map_or(now, |at| at.max(now));
+            self.next_at = Some(next_at + quota.interval);
+        }
+        match self.paused_until {
+            Some(until) if until > ready_at => until,
+            _ => ready_at,
+        }
+    }
+}
+
+/// Клиентский ограничитель частоты запросов с отдельной квотой на группу эндпоинтов.
+///
+/// Запросы сверх квоты не отклоняются, а ждут своей очереди. Ответ 429 с `Retry-After`
+/// приостанавливает всю группу на указанное время. Клоны разделяют состояние, поэтому
+/// один ограничитель можно передать нескольким клиентам.
+///
+/// Ограничитель подключается только явно через
+/// [`LavaTopClientBuilder::rate_limiter`](crate::client::LavaTopClientBuilder::rate_limiter).
+/// Lava Top не публикует квоты API, поэтому по умолчанию квот нет: задайте их сами
+/// через [`RateLimiter::quota`] или [`RateLimiter::per_group`].
+///
+/// ```no_run
+/// # use lava_top_rs::client::LavaTopClient;
+/// # use lava_top_rs::rate_limit::{EndpointGroup, Quota, RateLimiter};
+/// # fn main() -> Result<(), lava_top_rs::error::LavaTopError> {
+/// let limiter = RateLimiter::new()
+///     .quota(EndpointGroup::Invoices, Quota::per_second(5).burst(20))
+///     .unlimited(EndpointGroup::Other);
+/// let client = LavaTopClient::builder()
+///     .api_key("my-api-key")
+///     .rate_limiter(limiter)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<[Bucket; 4]>>,
+    max_pause: Duration,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::with_quota(None)
+    }
+}
+
+impl RateLimiter {
+    /// Ограничитель без квот: только паузы по `Retry-After` на ответ 429.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ограничитель с одинаковой квотой для каждой группы эндпоинтов.
+    pub fn per_group(quota: Quota) -> Self {
+        Self::with_quota(Some(quota))
+    }
+
+    fn with_quota(quota: Option<Quota>) -> Self {
+        let buckets = EndpointGroup::ALL.map(|_| Bucket {
+            quota,
+            next_at: None,
+            paused_until: None,
+        });
+        Self {
+            buckets: Arc::new(Mutex::new(buckets)),
+            max_pause: DEFAULT_MAX_PAUSE,
+        }
+    }
+
+    /// Квота для группы эндпоинтов.
+    pub fn quota(self, group: EndpointGroup, quota: Quota) -> Self {
+        self.buckets()[group.index()].quota = Some(quota);
+        self
+    }
+
+    /// Снимает ограничение с группы эндпоинтов.
+    pub fn unlimited(self, group: EndpointGroup) -> Self {
+        self.buckets()[group.index()].quota = None;
+        self
+    }
+
+    /// Максимальная пауза группы по `Retry-After`; большие значения сокращаются до нее.
+    pub fn max_pause(mut self, max: Duration) -> Self {
+        self.max_pause = max;
+        self
+    }
+
+    /// Ждет, пока запрос группы можно будет отправить.
+    pub async fn acquire(&self, group: EndpointGroup) {
+        let ready_at = self.buckets()[group.index()].reserve(Instant::now());
+        let wait = ready_at.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            crate::runtime::sleep(wait).await;
+        }
+    }
+
+    /// Приостанавливает группу на `duration`, но не дольше [`RateLimiter::max_pause`]
+    /// (вызывается при ответе 429).
+    pub fn pause(&self, group: EndpointGroup, duration: Duration) {
+        let Some(until) = Instant::now().checked_add(duration.min(self.max_pause)) else {
+            return;
+        };
+        let bucket = &mut self.buckets()[group.index()];
+        bucket.paused_until = Some(bucket.paused_until.map_or(until, |at| at.max(until)));
+    }
+
+    fn buckets(&self) -> std::sync::MutexGuard<'_, [Bucket; 4]> {
+        self.buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Слой, применяющий [`RateLimiter`] к каждой попытке запроса.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    limiter: RateLimiter,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: RateLimiter) -> Self {
+        Self { limiter }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+/// Сервис, созданный [`RateLimitLayer`].
+#[derive(Debug, Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: RateLimiter,
+}
+
+impl<S> Service<HttpRequest> for RateLimit<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Готовность внутреннего сервиса проверяется после ожидания очереди
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let inner = self.inner.clone();
+        let limiter = self.limiter.clone();
+        Box::pin(async move {
+            let group = request
+                .extensions()
+                .get::<EndpointGroup>()
+                .copied()
+                .unwrap_or(EndpointGroup::Other);
+            limiter.acquire(group).await;
+            let response = inner.oneshot(request).await?;
+            if response.status() == StatusCode::TOO_MANY_REQUESTS
+                && let Some(retry_after) = parse_retry_after(response.headers())
+            {
+                limiter.pause(group, retry_after);
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn bucket(quota: Option<Quota>) -> Bucket {
+        Bucket {
+            quota,
+            next_at: None,
+            paused_until: None,
+        }
+    }
+
+    #[test]
+    fn gcra_allows_burst_then_spaces_requests() {
+        let now = Instant::now();
+        let mut bucket = bucket(Some(Quota::per_second(10).burst(3)));
+        let delays: Vec<Duration> = (0..5).map(|_| bucket.reserve(now) - now).collect();
+        assert_eq!(
+            delays,
+            [
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+                100 * MS,
+                200 * MS
+            ]
+        );
+    }
+
+    #[test]
+    fn gcra_refills_after_idle() {
+        let now = Instant::now();
+        let mut bucket = bucket(Some(Quota::per_second(10).burst(2)));
+        for _ in 0..4 {
+            bucket.reserve(now);
+        }
+        // Очередь занята до now + 400ms; через секунду всплеск доступен снова
+        let later = now + Duration::from_secs(1);
+        assert_eq!(bucket.reserve(later), later);
+        assert_eq!(bucket.reserve(later), later);
+        assert_eq!(bucket.reserve(later), later + 100 * MS);
+    }
+
+    #[test]
+    fn no_quota_only_pauses() {
+        let now = Instant::now();
+        let mut bucket = bucket(None);
+        assert_eq!(bucket.reserve(now), now);
+        bucket.paused_until = Some(now + 50 * MS);
+        assert_eq!(bucket.reserve(now), now + 50 * MS);
+        assert_eq!(bucket.reserve(now + 60 * MS), now + 60 * MS);
+    }
+
+    #[test]
+    fn quota_clamps_to_one() {
+        assert_eq!(Quota::per(0, Duration::from_secs(1)), Quota::per_second(1));
+        assert_eq!(
+            Quota::per_minute(60).burst(0),
+            Quota::per_second(1).burst(1)
+        );
+    }
+
+    #[test]
+    fn pause_is_capped_and_never_shortened() {
+        let limiter = RateLimiter::new().max_pause(100 * MS);
+        let before = Instant::now();
+        limiter.pause(EndpointGroup::Sales, Duration::from_secs(3600));
+        let until = limiter.buckets()[EndpointGroup::Sales.index()]
+            .paused_until
+            .unwrap();
+        assert!(until <= Instant::now() + 100 * MS);
+        assert!(until >= before + 100 * MS);
+
+        limiter.pause(EndpointGroup::Sales, MS);
+        let buckets = limiter.buckets();
+        assert_eq!(
+            buckets[EndpointGroup::Sales.index()].paused_until,
+            Some(until)
+        );
+        assert!(
+            buckets[EndpointGroup::Invoices.index()]
+                .paused_until
+                .is_none()
+        );
+
+        // Переполнение `Instant` не паникует
+        drop(buckets);
+        RateLimiter::new()
+            .max_pause(Duration::MAX)
+            .pause(EndpointGroup::Other, Duration::MAX);
+    }
+
+    #[test]
+    fn endpoint_groups() {
+        for (endpoint, group) in [
+            ("/api/v1/invoice", EndpointGroup::Invoices),
+            ("/api/v2/invoice", EndpointGroup::Invoices),
+            ("/api/v1/invoices", EndpointGroup::Invoices),
+            (
+                "/api/v1/invoices/7ea82675-4ded-4133-95a7-a6efbaf165cc",
+                EndpointGroup::Invoices,
+            ),
+            ("/api/v1/sales/", EndpointGroup::Sales),
+            ("/api/v1/sales/72d53efb", EndpointGroup::Sales),
+            ("/api/v2/products", EndpointGroup::Products),
+            ("/api/v2/products/72d53efb", EndpointGroup::Products),
+            ("/api/v1/feed", EndpointGroup::Products),
+            ("/api/v1/subscriptions", EndpointGroup::Other),
+            ("/api/v1/donate", EndpointGroup::Other),
+            ("/api/v1/invoicesX", EndpointGroup::Other),
+            ("/api/v1/salesforce", EndpointGroup::Other),
+            ("/api/v1/subscriptions/invoices", EndpointGroup::Other),
+            ("/proxy/api/v1/invoices", EndpointGroup::Other),
+            ("/invoices", EndpointGroup::Other),
+            ("", EndpointGroup::Other),
+        ] {
+            assert_eq!(EndpointGroup::for_endpoint(endpoint), group, "{endpoint}");
+        }
+    }
+
+    fn request(group: Option<EndpointGroup>) -> HttpRequest {
+        let mut request = http::Request::builder()
+            .uri("http://localhost/api/v1/invoices")
+            .body(Bytes::new())
+            .unwrap();
+        if let Some(group) = group {
+            request.extensions_mut().insert(group);
+        }
+        request
+    }
+
+    /// Сервис, отвечающий 429 с `Retry-After: 1`.
+    fn too_many_requests()
+    -> impl Service<HttpRequest, Response = HttpResponse, Error = BoxError, Future: Send>
+    + Clone
+    + Send
+    + 'static {
+        tower::service_fn(|_request: HttpRequest| async {
+            Ok::<_, BoxError>(
+                http::Response::builder()
+                    .status(StatusCode::TOO_MANY_REQUESTS)
+                    .header(reqwest::header::RETRY_AFTER, "1")
+                    .body(Bytes::new())
+                    .unwrap(),
+            )
+        })
+    }
+
+    #[tokio::test]
+    async fn too_many_requests_pauses_tagged_group() {
+        let limiter = RateLimiter::new().max_pause(50 * MS);
+        let service = RateLimitLayer::new(limiter.clone()).layer(too_many_requests());
+
+        let response = service
+            .clone()
+            .oneshot(request(Some(EndpointGroup::Invoices)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let start = Instant::now();
+        limiter.acquire(EndpointGroup::Products).await;
+        limiter.acquire(EndpointGroup::Other).await;
+        assert!(start.elapsed() < 50 * MS);
+
+        limiter.acquire(EndpointGroup::Invoices).await;
+        assert!(start.elapsed() >= 40 * MS);
+    }
+
+    #[tokio::test]
+    async fn untagged_requests_count_as_other() {
+        let limiter = RateLimiter::new().max_pause(Duration::from_secs(60));
+        let service = RateLimitLayer::new(limiter.clone()).layer(too_many_requests());
+        service.oneshot(request(None)).await.unwrap();
+
+        let buckets = limiter.buckets();
+        assert!(buckets[EndpointGroup::Other.index()].paused_until.is_some());
+        assert!(
+            buckets[EndpointGroup::Invoices.index()]
+                .paused_until
+                .is_none()
+        );
+    }
+}
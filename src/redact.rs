@@ -0,0 +1,93 @@
+use reqwest::header::{AUTHORIZATION, HeaderMap};
+use serde_json::Value;
+use std::borrow::Cow;
+use url::Url;
+
+/// Замена скрытых значений в логах.
+const REDACTED: &str = "***";
+
+/// Поля JSON и параметры запроса с персональными данными покупателя.
+const SENSITIVE_FIELDS: &[&str] = &[
+    "email",
+    "buyerEmail",
+    "cardMask",
+    "last4CardDigits",
+    "search",
+];
+
+fn is_sensitive(name: &str) -> bool {
+    SENSITIVE_FIELDS
+        .iter()
+        .any(|field| field.eq_ignore_ascii_case(name))
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive(key) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Тело запроса или ответа для логов: почта и маска карты скрыты.
+pub(crate) fn body(bytes: &[u8]) -> Cow<'static, str> {
+    if bytes.is_empty() {
+        return Cow::Borrowed("");
+    }
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            Cow::Owned(value.to_string())
+        }
+        Err(_) => Cow::Owned(format!("<{} байт, не JSON>", bytes.len())),
+    }
+}
+
+/// URL для логов: значения чувствительных параметров запроса скрыты.
+pub(crate) fn url(url: &Url) -> String {
+    if url.query().is_none() {
+        return url.to_string();
+    }
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| {
+            let value = if is_sensitive(&key) {
+                REDACTED.to_string()
+            } else {
+                value.into_owned()
+            };
+            (key.into_owned(), value)
+        })
+        .collect();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
+
+/// Заголовки для логов: `X-Api-Key`, `Authorization` и прочие чувствительные значения скрыты.
+pub(crate) fn headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if value.is_sensitive()
+                || name == AUTHORIZATION
+                || name
+                    .as_str()
+                    .eq_ignore_ascii_case(crate::client::API_KEY_HEADER)
+            {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
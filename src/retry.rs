@@ -0,0 +1,521 @@
+use crate::error::{BoxError, LavaTopError};
+use crate::transport::{HttpRequest, HttpResponse, clone_request};
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use std::error::Error as StdError;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::{Layer, Service, ServiceExt};
+
+/// Когда допустимо повторять запрос с данным HTTP методом.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MethodRetry {
+    /// Повторять при любой временной ошибке (идемпотентные запросы).
+    Always,
+    /// Повторять, только если сервер гарантированно не начал обработку запроса:
+    /// соединение не было установлено или получен ответ 429.
+    OnlyIfNotProcessed,
+    /// Никогда не повторять.
+    Never,
+}
+
+/// Политика повторных попыток для временных ошибок сети и API.
+///
+/// Задержка между попытками растет экспоненциально (с джиттером), а заголовок
+/// `Retry-After` имеет приоритет над расчетной задержкой.
+///
+/// По умолчанию: 3 попытки, задержка от 200 мс до 5 с, повторяются статусы
+/// 408, 429, 500, 502, 503, 504. GET, HEAD, OPTIONS, PUT и DELETE повторяются при любой
+/// временной ошибке, POST и PATCH (например, `create_invoice_v2`) — только в безопасных случаях,
+/// см. [`MethodRetry::OnlyIfNotProcessed`].
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    respect_retry_after: bool,
+    max_retry_after: Duration,
+    retryable_statuses: Vec<StatusCode>,
+    method_overrides: Vec<(Method, MethodRetry)>,
+}
+
+/// Статусы временных ошибок, которые по умолчанию повторяются.
+pub(crate) const RETRYABLE_STATUSES: [StatusCode; 6] = [
+    StatusCode::REQUEST_TIMEOUT,
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+            respect_retry_after: true,
+            max_retry_after: Duration::from_secs(60),
+            retryable_statuses: RETRYABLE_STATUSES.to_vec(),
+            method_overrides: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Создает политику с параметрами по умолчанию.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Политика без повторных попыток.
+    pub fn disabled() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Максимальное число попыток, включая первую. Значение 0 трактуется как 1.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Задержка перед второй попыткой.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Верхняя граница расчетной задержки.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Множитель роста задержки. Значения меньше 1 трактуются как 1.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Включает или отключает случайный разброс задержки.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Учитывать ли заголовок `Retry-After` в ответе сервера.
+    pub fn respect_retry_after(mut self, enabled: bool) -> Self {
+        self.respect_retry_after = enabled;
+        self
+    }
+
+    /// Максимальное время ожидания по `Retry-After`. Если сервер просит ждать дольше,
+    /// запрос не повторяется и ошибка возвращается вызывающему коду.
+    pub fn max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+
+    /// Заменяет список HTTP статусов, при которых запрос повторяется.
+    pub fn retryable_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Переопределяет правило повтора для HTTP метода.
+    pub fn method(mut self, method: Method, retry: MethodRetry) -> Self {
+        self.method_overrides.retain(|(m, _)| *m != method);
+        self.method_overrides.push((method, retry));
+        self
+    }
+
+    /// Максимальное число попыток, включая первую.
+    pub fn attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Правило повтора для HTTP метода.
+    pub fn method_retry(&self, method: &Method) -> MethodRetry {
+        if let Some((_, retry)) = self.method_overrides.iter().find(|(m, _)| m == method) {
+            return *retry;
+        }
+        match *method {
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE => {
+                MethodRetry::Always
+            }
+            _ => MethodRetry::OnlyIfNotProcessed,
+        }
+    }
+
+    /// Нужно ли повторить запрос, завершившийся ответом с данным статусом.
+    pub(crate) fn should_retry_status(&self, method: &Method, status: StatusCode) -> bool {
+        if !self.retryable_statuses.contains(&status) {
+            return false;
+        }
+        match self.method_retry(method) {
+            MethodRetry::Always => true,
+            // 429 означает, что запрос был отклонен до обработки
+            MethodRetry::OnlyIfNotProcessed => status == StatusCode::TOO_MANY_REQUESTS,
+            MethodRetry::Never => false,
+        }
+    }
+
+    /// Нужно ли повторить запрос, завершившийся ошибкой транспорта.
+    ///
+    /// Повторяются сетевые ошибки reqwest и [`LavaTopError::Timeout`]; ошибки прочих
+    /// слоев считаются окончательными.
+    pub(crate) fn should_retry_error(
+        &self,
+        method: &Method,
+        error: &(dyn StdError + Send + Sync + 'static),
+    ) -> bool {
+        let method_retry = self.method_retry(method);
+        if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+            if error.is_builder() || error.is_redirect() || error.is_decode() {
+                return false;
+            }
+            return match method_retry {
+                MethodRetry::Always => {
+                    error.is_connect() || error.is_timeout() || error.is_request()
+                }
+                MethodRetry::OnlyIfNotProcessed => error.is_connect(),
+                MethodRetry::Never => false,
+            };
+        }
+        matches!(error.downcast_ref(), Some(LavaTopError::Timeout(_)))
+            && method_retry == MethodRetry::Always
+    }
+
+    /// Задержка перед попыткой `attempt + 1`.
+    ///
+    /// Возвращает `None`, если `Retry-After` превышает допустимый максимум.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if self.respect_retry_after
+            && let Some(retry_after) = retry_after
+        {
+            return (retry_after <= self.max_retry_after).then_some(retry_after);
+        }
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        let backoff = if self.jitter {
+            // "Equal jitter": половина задержки фиксирована, половина случайна
+            backoff / 2.0 + fastrand::f64() * backoff / 2.0
+        } else {
+            backoff
+        };
+        Some(Duration::from_secs_f64(backoff))
+    }
+}
+
+/// Разбирает заголовок `Retry-After` (число секунд или HTTP дата).
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    Some((date - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Слой, повторяющий запросы согласно [`RetryPolicy`].
+///
+/// Должен находиться снаружи [`TimeoutLayer`](crate::transport::TimeoutLayer), чтобы
+/// таймаут ограничивал каждую попытку, а не весь вызов.
+#[derive(Debug, Clone)]
+pub struct RetryLayer {
+    policy: RetryPolicy,
+}
+
+impl RetryLayer {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy }
+    }
+}
+
+impl<S> Layer<S> for RetryLayer {
+    type Service = Retry<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Retry {
+            inner,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// Сервис, созданный [`RetryLayer`].
+#[derive(Debug, Clone)]
+pub struct Retry<S> {
+    inner: S,
+    policy: RetryPolicy,
+}
+
+impl<S> Service<HttpRequest> for Retry<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        // Готовый сервис забираем себе, а на его место ставим клон (стандартный прием tower)
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let method = request.method().clone();
+            let mut attempt = 1;
+            loop {
+                let can_retry = attempt < policy.attempts();
+                let attempt_request = clone_request(&request);
+                #[cfg(feature = "tracing")]
+                tracing::Span::current().record("attempt", attempt);
+                // Первая попытка использует уже готовый сервис, последующие ждут готовности
+                let result = if attempt == 1 {
+                    inner.call(attempt_request).await
+                } else {
+                    inner.ready().await?.call(attempt_request).await
+                };
+                let delay = match &result {
+                    Ok(response)
+                        if can_retry && policy.should_retry_status(&method, response.status()) =>
+                    {
+                        policy.delay(attempt, parse_retry_after(response.headers()))
+                    }
+                    Err(error)
+                        if can_retry && policy.should_retry_error(&method, error.as_ref()) =>
+                    {
+                        policy.delay(attempt, None)
+                    }
+                    _ => None,
+                };
+                match delay {
+                    Some(delay) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(
+                            attempt,
+                            delay_ms = delay.as_millis() as u64,
+                            status = result.as_ref().ok().map(|r| r.status().as_u16()),
+                            error = result.as_ref().err().map(tracing::field::display),
+                            "повтор запроса"
+                        );
+                        crate::runtime::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return result,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use reqwest::header::HeaderValue;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(1))
+            .jitter(false)
+    }
+
+    fn request(method: Method) -> HttpRequest {
+        http::Request::builder()
+            .method(method)
+            .uri("http://localhost/api/v1/invoices")
+            .body(Bytes::new())
+            .unwrap()
+    }
+
+    /// Сервис, отвечающий статусами по очереди (последний повторяется), и счетчик вызовов.
+    fn responder(
+        statuses: &'static [u16],
+    ) -> (
+        impl Service<HttpRequest, Response = HttpResponse, Error = BoxError, Future: Send>
+        + Clone
+        + Send
+        + 'static,
+        Arc<AtomicUsize>,
+    ) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let service = tower::service_fn(move |_request: HttpRequest| {
+            let call = counter.fetch_add(1, Ordering::SeqCst);
+            let status = statuses[call.min(statuses.len() - 1)];
+            async move {
+                Ok::<_, BoxError>(
+                    http::Response::builder()
+                        .status(status)
+                        .body(Bytes::new())
+                        .unwrap(),
+                )
+            }
+        });
+        (service, calls)
+    }
+
+    #[test]
+    fn idempotent_methods_retry_every_retryable_status() {
+        let policy = RetryPolicy::new();
+        for status in RETRYABLE_STATUSES {
+            assert!(policy.should_retry_status(&Method::GET, status), "{status}");
+            assert!(
+                policy.should_retry_status(&Method::DELETE, status),
+                "{status}"
+            );
+        }
+        assert!(!policy.should_retry_status(&Method::GET, StatusCode::BAD_REQUEST));
+        assert!(!policy.should_retry_status(&Method::GET, StatusCode::NOT_IMPLEMENTED));
+    }
+
+    #[test]
+    fn non_idempotent_methods_retry_only_rejected_requests() {
+        let policy = RetryPolicy::new();
+        assert!(policy.should_retry_status(&Method::POST, StatusCode::TOO_MANY_REQUESTS));
+        assert!(!policy.should_retry_status(&Method::POST, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.should_retry_status(&Method::PATCH, StatusCode::BAD_GATEWAY));
+    }
+
+    #[test]
+    fn method_override_replaces_default_rule() {
+        let policy = RetryPolicy::new()
+            .method(Method::GET, MethodRetry::Never)
+            .method(Method::POST, MethodRetry::Always);
+        assert_eq!(policy.method_retry(&Method::GET), MethodRetry::Never);
+        assert!(!policy.should_retry_status(&Method::GET, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.should_retry_status(&Method::POST, StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[test]
+    fn timeout_errors_are_retried_for_idempotent_methods_only() {
+        let policy = RetryPolicy::new();
+        let timeout = LavaTopError::Timeout(Duration::from_secs(1));
+        assert!(policy.should_retry_error(&Method::GET, &timeout));
+        assert!(!policy.should_retry_error(&Method::POST, &timeout));
+        let other = std::io::Error::other("boom");
+        assert!(!policy.should_retry_error(&Method::GET, &other));
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_max() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350))
+            .jitter(false);
+        assert_eq!(policy.delay(1, None), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(2, None), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(3, None), Some(Duration::from_millis(350)));
+        assert_eq!(
+            policy.delay(u32::MAX, None),
+            Some(Duration::from_millis(350))
+        );
+    }
+
+    #[test]
+    fn jitter_keeps_at_least_half_of_backoff() {
+        let policy = RetryPolicy::new().initial_backoff(Duration::from_millis(100));
+        for _ in 0..100 {
+            let delay = policy.delay(1, None).unwrap();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn retry_after_overrides_backoff_within_limit() {
+        let policy = policy().max_retry_after(Duration::from_secs(10));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(3))),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(11))), None);
+        let ignoring = policy.respect_retry_after(false);
+        assert_eq!(
+            ignoring.delay(1, Some(Duration::from_secs(11))),
+            Some(Duration::from_millis(1))
+        );
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(" 7 "));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+        let future = (Utc::now() + chrono::Duration::seconds(120)).to_rfc2822();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&future).unwrap());
+        let delay = parse_retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(100) && delay <= Duration::from_secs(120));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let (service, calls) = responder(&[503, 502, 200]);
+        let response = RetryLayer::new(policy())
+            .layer(service)
+            .oneshot(request(Method::GET))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_attempts() {
+        let (service, calls) = responder(&[503]);
+        let response = RetryLayer::new(policy().max_attempts(2))
+            .layer(service)
+            .oneshot(request(Method::GET))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn does_not_repeat_processed_post() {
+        let (service, calls) = responder(&[503, 200]);
+        let response = RetryLayer::new(policy())
+            .layer(service)
+            .oneshot(request(Method::POST))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let (service, calls) = responder(&[429, 201]);
+        let response = RetryLayer::new(policy())
+            .layer(service)
+            .oneshot(request(Method::POST))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
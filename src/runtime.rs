@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+/// Истек таймаут [`timeout`].
+#[derive(Debug)]
+pub(crate) struct Elapsed;
+
+/// Ждет `duration`: на таймере tokio с feature `tokio`, иначе на `futures-timer`,
+/// который работает с любым исполнителем.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+    #[cfg(not(feature = "tokio"))]
+    futures_timer::Delay::new(duration).await;
+}
+
+/// Выполняет `future`, ограничивая его время ожидания `duration`.
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, Elapsed> {
+    #[cfg(feature = "tokio")]
+    {
+        tokio::time::timeout(duration, future)
+            .await
+            .map_err(|_| Elapsed)
+    }
+    #[cfg(not(feature = "tokio"))]
+    {
+        use futures::future::{Either, select};
+        let future = std::pin::pin!(future);
+        match select(future, futures_timer::Delay::new(duration)).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(_) => Err(Elapsed),
+        }
+    }
+}
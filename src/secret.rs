@@ -0,0 +1,123 @@
+use crate::error::{BoxError, LavaTopError};
+use futures::future::BoxFuture;
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroize;
+
+/// Строка с секретом (API ключ, пароль), которая не попадает в логи.
+///
+/// `Debug` выводит `***`, `Display` не реализован, а память затирается при удалении.
+/// Значение доступно только явно, через [`SecretString::expose_secret`].
+///
+/// ```
+/// # use lava_top_rs::secret::SecretString;
+/// let key = SecretString::from("my-api-key");
+/// assert_eq!(format!("{key:?}"), "***");
+/// assert_eq!(key.expose_secret(), "my-api-key");
+/// ```
+#[derive(Clone, Default)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    /// Значение секрета.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// Пустой ли секрет (без учета пробельных символов).
+    pub fn is_blank(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self(secret.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Источник API ключа, который запрашивается перед каждым вызовом API.
+///
+/// Подходит для ключей из хранилища секретов с ротацией: провайдер сам решает, когда
+/// обновлять закэшированное значение. Реализован для замыканий
+/// `Fn() -> impl Future<Output = Result<SecretString, BoxError>>`.
+pub trait ApiKeyProvider: Send + Sync {
+    /// Текущий API ключ.
+    fn api_key(&self) -> BoxFuture<'_, Result<SecretString, BoxError>>;
+}
+
+impl<F, Fut> ApiKeyProvider for F
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<SecretString, BoxError>> + Send + 'static,
+{
+    fn api_key(&self) -> BoxFuture<'_, Result<SecretString, BoxError>> {
+        Box::pin(self())
+    }
+}
+
+/// API ключ клиента: фиксированный или получаемый от провайдера.
+#[derive(Clone)]
+pub(crate) enum ApiKey {
+    Static(SecretString),
+    Provider(Arc<dyn ApiKeyProvider>),
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKey::Static(secret) => f.debug_tuple("Static").field(secret).finish(),
+            ApiKey::Provider(_) => f.write_str("Provider(..)"),
+        }
+    }
+}
+
+impl ApiKey {
+    /// Значение заголовка `X-Api-Key`, помеченное как чувствительное.
+    pub(crate) async fn header_value(&self) -> Result<HeaderValue, LavaTopError> {
+        let secret = match self {
+            ApiKey::Static(secret) => return header_value(secret),
+            ApiKey::Provider(provider) => provider.api_key().await.map_err(LavaTopError::ApiKey)?,
+        };
+        if secret.is_blank() {
+            return Err(LavaTopError::MissingParameter("api_key".to_string()));
+        }
+        header_value(&secret)
+    }
+}
+
+pub(crate) fn header_value(secret: &SecretString) -> Result<HeaderValue, LavaTopError> {
+    let mut value = HeaderValue::from_str(secret.expose_secret())?;
+    value.set_sensitive(true);
+    Ok(value)
+}
@@ -0,0 +1,573 @@
+use crate::client::LavaTopClient;
+use crate::error::LavaTopError;
+use crate::models::common::{CurrencyDto, InvoiceStatus, InvoiceType, Periodicity};
+use crate::models::contract::ContractState;
+use crate::models::invoice::{InvoiceResponseV2, ListInvoicesParams};
+use crate::models::subscription::CancelSubscriptionParams;
+use crate::models::webhook::{PaymentEvent, WebhookEvent};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Платеж по подписке: первая покупка или очередное списание.
+#[derive(Debug, Clone)]
+pub struct SubscriptionPayment {
+    /// Идентификатор контракта платежа (для первой покупки совпадает с подпиской).
+    pub contract_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub amount: Option<f64>,
+    pub currency: Option<CurrencyDto>,
+    pub succeeded: bool,
+    /// Сообщение об ошибке неуспешного платежа.
+    pub error_message: Option<String>,
+}
+
+impl SubscriptionPayment {
+    fn from_event(event: &PaymentEvent, succeeded: bool) -> Self {
+        Self {
+            contract_id: event.contract_id,
+            timestamp: event.timestamp,
+            amount: Some(event.amount),
+            currency: Some(event.currency.clone()),
+            succeeded,
+            error_message: event.error_message.clone(),
+        }
+    }
+
+    /// Платеж по контракту; `None`, пока контракт не получил итоговый статус.
+    fn from_invoice(invoice: &InvoiceResponseV2) -> Option<Self> {
+        let succeeded = match invoice.status {
+            InvoiceStatus::Completed => true,
+            InvoiceStatus::Failed => false,
+            _ => return None,
+        };
+        Some(Self {
+            contract_id: invoice.id,
+            timestamp: invoice.datetime,
+            amount: invoice.receipt.as_ref().map(|r| r.amount),
+            currency: invoice.receipt.as_ref().map(|r| r.currency.clone()),
+            succeeded,
+            error_message: None,
+        })
+    }
+}
+
+/// Идентификатор родительского контракта, к которому может относиться событие.
+///
+/// Для `payment_success`/`payment_failed` это сам контракт: событие относится к подписке,
+/// только если это ее первая покупка.
+fn subscription_id(event: &WebhookEvent) -> Option<Uuid> {
+    match event {
+        WebhookEvent::PaymentSuccess(e) | WebhookEvent::PaymentFailed(e) => Some(e.contract_id),
+        WebhookEvent::SubscriptionRecurringPaymentSuccess(e)
+        | WebhookEvent::SubscriptionRecurringPaymentFailed(e) => Some(e.parent_contract_id),
+        WebhookEvent::SubscriptionCancelled(e) => {
+            Some(e.parent_contract_id.unwrap_or(e.contract_id))
+        }
+        WebhookEvent::Unknown(_) => None,
+    }
+}
+
+/// Подписка, собранная из контрактов и вебхуков по идентификатору родительского контракта.
+///
+/// И контракты из API, и события вебхуков меняют состояние только по
+/// [таблице переходов](ContractState::can_reach), поэтому запоздавший снимок или
+/// событие не откатывают подписку назад (например, из отмененной в активную).
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    parent_contract_id: Uuid,
+    state: ContractState,
+    email: Option<String>,
+    product: Option<String>,
+    periodicity: Option<Periodicity>,
+    payments: Vec<SubscriptionPayment>,
+    cancelled_at: Option<DateTime<Utc>>,
+    will_expire_at: Option<DateTime<Utc>>,
+}
+
+impl Subscription {
+    /// Пустая подписка в состоянии [`ContractState::New`].
+    pub fn new(parent_contract_id: Uuid) -> Self {
+        Self {
+            parent_contract_id,
+            state: ContractState::New,
+            email: None,
+            product: None,
+            periodicity: None,
+            payments: Vec::new(),
+            cancelled_at: None,
+            will_expire_at: None,
+        }
+    }
+
+    /// Учитывает контракт подписки или очередного списания по ней.
+    ///
+    /// Возвращает `false`, если контракт не относится к этой подписке.
+    pub fn apply_invoice(&mut self, invoice: &InvoiceResponseV2) -> bool {
+        let is_parent = invoice.id == self.parent_contract_id;
+        if is_parent {
+            // Завершенная первая покупка подписки означает, что подписка активна
+            self.advance(match invoice.state() {
+                ContractState::Completed => ContractState::SubscriptionActive,
+                state => state,
+            });
+            if let Some(product) = &invoice.product {
+                self.product = product.name.clone().or(self.product.take());
+            }
+            if let Some(details) = &invoice.subscription_details {
+                self.cancelled_at = details.cancelled_at.or(self.cancelled_at);
+                self.will_expire_at = details.expired_at.or(self.will_expire_at);
+            }
+        } else if invoice.parent_invoice.as_ref().map(|p| p.id) != Some(self.parent_contract_id) {
+            return false;
+        }
+        if self.email.is_none() {
+            self.email = invoice.buyer.as_ref().map(|b| b.email.clone());
+        }
+        if let Some(mut payment) = SubscriptionPayment::from_invoice(invoice) {
+            // Статус родительского контракта отражает подписку, а не первую покупку:
+            // раз подписка была активирована, первая оплата прошла
+            if is_parent && invoice.subscription_status.is_some() {
+                payment.succeeded = true;
+            }
+            self.record(payment);
+        }
+        true
+    }
+
+    /// Учитывает событие вебхука.
+    ///
+    /// Возвращает `false`, если событие не относится к этой подписке.
+    pub fn apply_event(&mut self, event: &WebhookEvent) -> bool {
+        if subscription_id(event) != Some(self.parent_contract_id) {
+            return false;
+        }
+        match event {
+            WebhookEvent::PaymentSuccess(e) | WebhookEvent::PaymentFailed(e) => {
+                let succeeded = matches!(event, WebhookEvent::PaymentSuccess(_));
+                self.record_event(e, succeeded);
+                self.advance(if succeeded {
+                    ContractState::SubscriptionActive
+                } else {
+                    ContractState::Failed
+                });
+            }
+            WebhookEvent::SubscriptionRecurringPaymentSuccess(e)
+            | WebhookEvent::SubscriptionRecurringPaymentFailed(e) => {
+                let succeeded =
+                    matches!(event, WebhookEvent::SubscriptionRecurringPaymentSuccess(_));
+                self.record_event(&e.payment, succeeded);
+                self.advance(if succeeded {
+                    ContractState::SubscriptionActive
+                } else {
+                    ContractState::SubscriptionFailed
+                });
+            }
+            WebhookEvent::SubscriptionCancelled(e) => {
+                if self.email.is_none() {
+                    self.email = e.buyer.as_ref().map(|b| b.email.clone());
+                }
+                self.cancelled_at = Some(e.cancelled_at);
+                self.will_expire_at = Some(e.will_expire_at);
+                self.advance(ContractState::SubscriptionCancelled);
+            }
+            WebhookEvent::Unknown(_) => return false,
+        }
+        true
+    }
+
+    /// Задает периодичность списаний по цене оффера (в контрактах и вебхуках ее нет).
+    ///
+    /// Без нее [`Subscription::next_charge_at`] и расчетная дата окончания недоступны.
+    pub fn set_periodicity(&mut self, periodicity: Periodicity) {
+        self.periodicity = Some(periodicity);
+    }
+
+    /// Отменяет подписку через [`LavaTopClient::cancel_subscription`].
+    ///
+    /// Требует email покупателя, известный из контракта или вебхука.
+    pub async fn cancel(&mut self, client: &LavaTopClient) -> Result<(), LavaTopError> {
+        let email = self
+            .email
+            .clone()
+            .ok_or_else(|| LavaTopError::MissingParameter("email".to_string()))?;
+        client
+            .cancel_subscription(&CancelSubscriptionParams {
+                contract_id: self.parent_contract_id,
+                email,
+            })
+            .await?;
+        self.cancelled_at = Some(Utc::now());
+        self.will_expire_at = self.will_expire_at.or_else(|| self.paid_until());
+        self.advance(ContractState::SubscriptionCancelled);
+        Ok(())
+    }
+
+    /// Идентификатор родительского контракта (первой покупки).
+    pub fn parent_contract_id(&self) -> Uuid {
+        self.parent_contract_id
+    }
+
+    /// Текущее состояние подписки.
+    pub fn state(&self) -> &ContractState {
+        &self.state
+    }
+
+    /// Email покупателя.
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    /// Название продукта.
+    pub fn product(&self) -> Option<&str> {
+        self.product.as_deref()
+    }
+
+    /// Периодичность списаний, заданная через [`Subscription::set_periodicity`].
+    pub fn periodicity(&self) -> Option<Periodicity> {
+        self.periodicity.clone()
+    }
+
+    /// История платежей в хронологическом порядке.
+    pub fn payments(&self) -> &[SubscriptionPayment] {
+        &self.payments
+    }
+
+    /// Последний успешный платеж.
+    pub fn last_payment(&self) -> Option<&SubscriptionPayment> {
+        self.payments.iter().rev().find(|p| p.succeeded)
+    }
+
+    /// Количество неуспешных платежей.
+    pub fn failed_payment_count(&self) -> usize {
+        self.payments.iter().filter(|p| !p.succeeded).count()
+    }
+
+    /// Дата отмены подписки.
+    pub fn cancelled_at(&self) -> Option<DateTime<Utc>> {
+        self.cancelled_at
+    }
+
+    /// Дата, до которой доступ оплачен: из события отмены или контракта, иначе
+    /// последний успешный платеж плюс период.
+    pub fn will_expire_at(&self) -> Option<DateTime<Utc>> {
+        self.will_expire_at.or_else(|| self.paid_until())
+    }
+
+    /// Ожидаемая дата следующего списания; только для действующей подписки.
+    pub fn next_charge_at(&self) -> Option<DateTime<Utc>> {
+        if self.state == ContractState::SubscriptionActive {
+            self.paid_until()
+        } else {
+            None
+        }
+    }
+
+    fn paid_until(&self) -> Option<DateTime<Utc>> {
+        self.periodicity()?
+            .period_end(self.last_payment()?.timestamp)
+    }
+
+    fn advance(&mut self, next: ContractState) {
+        if self.state.can_reach(&next) {
+            self.state = next;
+        }
+    }
+
+    fn record_event(&mut self, event: &PaymentEvent, succeeded: bool) {
+        if self.email.is_none() {
+            self.email = Some(event.buyer.email.clone());
+        }
+        if self.product.is_none() {
+            self.product = event.product.title.clone();
+        }
+        self.record(SubscriptionPayment::from_event(event, succeeded));
+    }
+
+    /// Добавляет платеж; повторный платеж по тому же контракту заменяет прежний.
+    fn record(&mut self, payment: SubscriptionPayment) {
+        match self
+            .payments
+            .iter_mut()
+            .find(|p| p.contract_id == payment.contract_id)
+        {
+            Some(existing) => *existing = payment,
+            None => self.payments.push(payment),
+        }
+        self.payments.sort_by_key(|p| p.timestamp);
+    }
+}
+
+/// Подписки по идентификатору родительского контракта.
+///
+/// Заполняется контрактами из [`SubscriptionTracker::load`] и событиями вебхуков по мере
+/// их получения.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionTracker {
+    subscriptions: HashMap<Uuid, Subscription>,
+}
+
+impl SubscriptionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Собирает подписки из всех контрактов, подходящих под `params`.
+    ///
+    /// Чтобы не загружать разовые покупки, передайте `invoice_types: Some(vec![InvoiceType::Recurring])`.
+    pub async fn load(
+        client: &LavaTopClient,
+        params: ListInvoicesParams,
+    ) -> Result<Self, LavaTopError> {
+        let mut tracker = Self::new();
+        client
+            .invoices_stream(params)
+            .try_for_each(|invoice| {
+                tracker.apply_invoice(&invoice);
+                futures::future::ready(Ok(()))
+            })
+            .await?;
+        Ok(tracker)
+    }
+
+    /// Учитывает контракт; разовые покупки пропускаются.
+    ///
+    /// Возвращает подписку, к которой относится контракт.
+    pub fn apply_invoice(&mut self, invoice: &InvoiceResponseV2) -> Option<&Subscription> {
+        let id = match &invoice.parent_invoice {
+            Some(parent) => parent.id,
+            None if invoice.invoice_type == InvoiceType::Recurring => invoice.id,
+            None => return None,
+        };
+        let subscription = self
+            .subscriptions
+            .entry(id)
+            .or_insert_with(|| Subscription::new(id));
+        subscription.apply_invoice(invoice);
+        Some(subscription)
+    }
+
+    /// Учитывает событие вебхука; платежи по разовым покупкам пропускаются.
+    ///
+    /// Возвращает подписку, к которой относится событие.
+    pub fn apply_event(&mut self, event: &WebhookEvent) -> Option<&Subscription> {
+        let id = subscription_id(event)?;
+        if let WebhookEvent::PaymentSuccess(e) | WebhookEvent::PaymentFailed(e) = event
+            && !self.subscriptions.contains_key(&id)
+            && !e.state().is_subscription()
+        {
+            return None;
+        }
+        let subscription = self
+            .subscriptions
+            .entry(id)
+            .or_insert_with(|| Subscription::new(id));
+        subscription.apply_event(event);
+        Some(subscription)
+    }
+
+    /// Подписка по идентификатору родительского контракта.
+    pub fn get(&self, parent_contract_id: &Uuid) -> Option<&Subscription> {
+        self.subscriptions.get(parent_contract_id)
+    }
+
+    /// Изменяемая ссылка на подписку, например для [`Subscription::cancel`].
+    pub fn get_mut(&mut self, parent_contract_id: &Uuid) -> Option<&mut Subscription> {
+        self.subscriptions.get_mut(parent_contract_id)
+    }
+
+    /// Все известные подписки в произвольном порядке.
+    pub fn iter(&self) -> impl Iterator<Item = &Subscription> {
+        self.subscriptions.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    const PARENT: &str = "d31384b8-e412-4be5-a2ec-297ae6666c8f";
+
+    fn parent() -> Uuid {
+        PARENT.parse().unwrap()
+    }
+
+    fn invoice(id: Uuid, invoice_type: &str, status: &str, extra: Value) -> InvoiceResponseV2 {
+        let mut value = json!({
+            "id": id,
+            "type": invoice_type,
+            "datetime": "2024-01-31T10:00:00Z",
+            "status": status,
+            "receipt": { "amount": 490.0, "currency": "RUB", "fee": null },
+            "buyer": { "email": "buyer@example.com", "cardMask": null },
+            "product": { "name": "Клуб", "offer": "Подписка" },
+        });
+        value
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn event(value: Value) -> WebhookEvent {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn charge(contract_id: Uuid, succeeded: bool, timestamp: &str) -> WebhookEvent {
+        event(json!({
+            "eventType": if succeeded {
+                "subscription_recurring_payment_success"
+            } else {
+                "subscription_recurring_payment_failed"
+            },
+            "product": { "id": Uuid::nil(), "title": "Клуб" },
+            "contractId": contract_id,
+            "parentContractId": PARENT,
+            "buyer": { "email": "buyer@example.com" },
+            "amount": 490.0,
+            "currency": "RUB",
+            "status": if succeeded { "subscription-active" } else { "subscription-failed" },
+            "timestamp": timestamp,
+        }))
+    }
+
+    fn cancelled() -> WebhookEvent {
+        event(json!({
+            "eventType": "subscription_cancelled",
+            "contractId": PARENT,
+            "cancelledAt": "2024-02-10T00:00:00Z",
+            "willExpireAt": "2024-02-29T10:00:00Z",
+        }))
+    }
+
+    #[test]
+    fn completed_parent_invoice_activates_subscription() {
+        let mut subscription = Subscription::new(parent());
+        assert!(subscription.apply_invoice(&invoice(
+            parent(),
+            "RECURRING",
+            "COMPLETED",
+            json!({})
+        )));
+        assert_eq!(subscription.state(), &ContractState::SubscriptionActive);
+        assert_eq!(subscription.email(), Some("buyer@example.com"));
+        assert_eq!(subscription.product(), Some("Клуб"));
+        assert_eq!(subscription.payments().len(), 1);
+        assert!(subscription.last_payment().unwrap().succeeded);
+
+        // Контракт другой подписки не учитывается
+        let other = invoice(Uuid::new_v4(), "RECURRING", "COMPLETED", json!({}));
+        assert!(!subscription.apply_invoice(&other));
+        assert_eq!(subscription.payments().len(), 1);
+    }
+
+    #[test]
+    fn stale_snapshot_does_not_reactivate_cancelled_subscription() {
+        let mut subscription = Subscription::new(parent());
+        subscription.apply_invoice(&invoice(parent(), "RECURRING", "COMPLETED", json!({})));
+        subscription.apply_event(&cancelled());
+        assert_eq!(subscription.state(), &ContractState::SubscriptionCancelled);
+
+        let stale = invoice(
+            parent(),
+            "RECURRING",
+            "COMPLETED",
+            json!({ "subscriptionStatus": "ACTIVE" }),
+        );
+        subscription.apply_invoice(&stale);
+        assert_eq!(subscription.state(), &ContractState::SubscriptionCancelled);
+        // Запоздавшее списание тоже не возвращает подписку в активные
+        subscription.apply_event(&charge(Uuid::new_v4(), true, "2024-02-01T00:00:00Z"));
+        assert_eq!(subscription.state(), &ContractState::SubscriptionCancelled);
+        assert_eq!(
+            subscription.will_expire_at(),
+            Some("2024-02-29T10:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn failed_charge_and_recovery() {
+        let mut subscription = Subscription::new(parent());
+        subscription.apply_invoice(&invoice(parent(), "RECURRING", "COMPLETED", json!({})));
+
+        let failed = Uuid::new_v4();
+        subscription.apply_event(&charge(failed, false, "2024-02-29T10:00:00Z"));
+        assert_eq!(subscription.state(), &ContractState::SubscriptionFailed);
+        assert_eq!(subscription.failed_payment_count(), 1);
+        assert_eq!(subscription.next_charge_at(), None);
+
+        let retried = Uuid::new_v4();
+        subscription.apply_event(&charge(retried, true, "2024-03-01T10:00:00Z"));
+        // Дубль вебхука не добавляет платеж
+        subscription.apply_event(&charge(retried, true, "2024-03-01T10:00:00Z"));
+        assert_eq!(subscription.state(), &ContractState::SubscriptionActive);
+        assert_eq!(subscription.payments().len(), 3);
+        assert_eq!(subscription.last_payment().unwrap().contract_id, retried);
+    }
+
+    #[test]
+    fn charge_dates_require_known_periodicity() {
+        let mut subscription = Subscription::new(parent());
+        subscription.apply_invoice(&invoice(parent(), "RECURRING", "COMPLETED", json!({})));
+        assert_eq!(subscription.periodicity(), None);
+        assert_eq!(subscription.next_charge_at(), None);
+        assert_eq!(subscription.will_expire_at(), None);
+
+        subscription.set_periodicity(Periodicity::Monthly);
+        let end: DateTime<Utc> = "2024-02-29T10:00:00Z".parse().unwrap();
+        assert_eq!(subscription.next_charge_at(), Some(end));
+        assert_eq!(subscription.will_expire_at(), Some(end));
+    }
+
+    #[test]
+    fn tracker_groups_charges_and_skips_one_time_purchases() {
+        let mut tracker = SubscriptionTracker::new();
+        assert!(
+            tracker
+                .apply_invoice(&invoice(Uuid::new_v4(), "ONE_TIME", "COMPLETED", json!({})))
+                .is_none()
+        );
+        let one_time_payment = event(json!({
+            "eventType": "payment_success",
+            "product": { "id": Uuid::nil(), "title": "Курс" },
+            "contractId": Uuid::new_v4(),
+            "buyer": { "email": "buyer@example.com" },
+            "amount": 990.0,
+            "currency": "RUB",
+            "status": "completed",
+            "timestamp": "2024-01-31T10:00:00Z",
+        }));
+        assert!(tracker.apply_event(&one_time_payment).is_none());
+        assert!(tracker.is_empty());
+
+        let charge_invoice = invoice(
+            Uuid::new_v4(),
+            "RECURRING",
+            "COMPLETED",
+            json!({ "datetime": "2024-02-29T10:00:00Z", "parentInvoice": { "id": PARENT } }),
+        );
+        tracker.apply_invoice(&charge_invoice);
+        tracker.apply_invoice(&invoice(parent(), "RECURRING", "COMPLETED", json!({})));
+        assert_eq!(tracker.len(), 1);
+        let subscription = tracker.get(&parent()).unwrap();
+        assert_eq!(subscription.payments().len(), 2);
+        assert_eq!(subscription.payments()[0].contract_id, parent());
+        assert_eq!(subscription.state(), &ContractState::SubscriptionActive);
+
+        tracker.apply_event(&cancelled());
+        assert_eq!(
+            tracker.get(&parent()).unwrap().state(),
+            &ContractState::SubscriptionCancelled
+        );
+    }
+}
@@ -0,0 +1,456 @@
+use crate::client::LavaTopClient;
+use crate::error::{Locale, Localized};
+use crate::models::common::{
+    ClientUtmDto, ContractStatusDto, CurrencyDto, InvoiceType, Periodicity, PriceDto, ProductType,
+    SubscriptionStatus,
+};
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderValue;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use url::Url;
+use uuid::Uuid;
+
+mod routes;
+mod state;
+
+use state::MockState;
+
+/// API ключ, который мок-сервер принимает по умолчанию.
+pub const DEFAULT_API_KEY: &str = "test-api-key";
+
+/// Ошибки управления состоянием мок-сервера.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum MockError {
+    /// Контракт не найден.
+    ContractNotFound(Uuid),
+    /// Переход недопустим в текущем состоянии контракта.
+    InvalidState {
+        id: Uuid,
+        status: ContractStatusDto,
+        /// Отклоненный метод [`MockLavaTop`] (например, `mark_invoice_paid`).
+        action: &'static str,
+    },
+    /// Не удалось доставить вебхук.
+    Webhook(#[from] reqwest::Error),
+    /// Ошибка запуска сервера.
+    Io(#[from] std::io::Error),
+}
+
+impl MockError {
+    /// Стабильный машиночитаемый код ошибки, не зависящий от языка.
+    pub fn code(&self) -> &'static str {
+        match self {
+            MockError::ContractNotFound(_) => "mock.contract_not_found",
+            MockError::InvalidState { .. } => "mock.invalid_state",
+            MockError::Webhook(_) => "mock.webhook",
+            MockError::Io(_) => "mock.io",
+        }
+    }
+
+    /// Сообщение об ошибке на языке `locale`.
+    pub fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            error: self,
+            locale,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let ru = locale == Locale::Ru;
+        match self {
+            MockError::ContractNotFound(id) if ru => write!(f, "Контракт не найден: {id}"),
+            MockError::ContractNotFound(id) => write!(f, "Contract not found: {id}"),
+            MockError::InvalidState { id, status, action } if ru => write!(
+                f,
+                "Недопустимый переход для контракта {id} в статусе {status}: {action}"
+            ),
+            MockError::InvalidState { id, status, action } => {
+                write!(
+                    f,
+                    "Invalid transition for contract {id} in status {status}: {action}"
+                )
+            }
+            // Причина доступна через `source()`
+            MockError::Webhook(_) if ru => f.write_str("Ошибка доставки вебхука"),
+            MockError::Webhook(_) => f.write_str("Webhook delivery failed"),
+            MockError::Io(_) if ru => f.write_str("Ошибка запуска мок-сервера"),
+            MockError::Io(_) => f.write_str("Failed to start the mock server"),
+        }
+    }
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, Locale::default())
+    }
+}
+
+impl fmt::Display for Localized<'_, MockError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write(f, self.locale)
+    }
+}
+
+/// Продукт в каталоге мок-сервера.
+#[derive(Debug, Clone)]
+pub struct MockProduct {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub product_type: ProductType,
+    pub offers: Vec<MockOffer>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MockProduct {
+    /// Новый продукт со случайным идентификатором.
+    pub fn new(title: impl Into<String>, product_type: ProductType) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            title: title.into(),
+            description: None,
+            product_type,
+            offers: Vec::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Добавляет оффер.
+    pub fn offer(mut self, offer: MockOffer) -> Self {
+        self.offers.push(offer);
+        self
+    }
+}
+
+/// Оффер продукта в каталоге мок-сервера.
+#[derive(Debug, Clone)]
+pub struct MockOffer {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub prices: Vec<PriceDto>,
+}
+
+impl MockOffer {
+    /// Новый оффер со случайным идентификатором и без цен.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            description: None,
+            prices: Vec::new(),
+        }
+    }
+
+    /// Добавляет цену.
+    pub fn price(mut self, currency: CurrencyDto, amount: f64, periodicity: Periodicity) -> Self {
+        self.prices.push(PriceDto {
+            amount: Some(amount),
+            currency,
+            periodicity: Some(periodicity),
+        });
+        self
+    }
+}
+
+/// Контракт, хранящийся в мок-сервере.
+#[derive(Debug, Clone)]
+pub struct MockContract {
+    pub id: Uuid,
+    /// Родительский контракт (для рекуррентных списаний).
+    pub parent_id: Option<Uuid>,
+    pub invoice_type: InvoiceType,
+    pub status: ContractStatusDto,
+    pub created_at: DateTime<Utc>,
+    pub email: String,
+    pub product_id: Uuid,
+    pub offer_id: Uuid,
+    pub currency: CurrencyDto,
+    pub amount: f64,
+    pub periodicity: Periodicity,
+    pub subscription_status: Option<SubscriptionStatus>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub will_expire_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub client_utm: Option<ClientUtmDto>,
+}
+
+/// Куда мок-сервер отправляет вебхуки.
+#[derive(Debug, Clone)]
+pub(crate) struct WebhookTarget {
+    url: Url,
+    api_key: Option<String>,
+}
+
+/// Построитель [`MockLavaTop`].
+#[derive(Debug)]
+pub struct MockLavaTopBuilder {
+    api_key: String,
+    webhook: Option<WebhookTarget>,
+    products_page_size: usize,
+    products: Vec<MockProduct>,
+}
+
+impl Default for MockLavaTopBuilder {
+    fn default() -> Self {
+        Self {
+            api_key: DEFAULT_API_KEY.to_string(),
+            webhook: None,
+            products_page_size: 20,
+            products: Vec::new(),
+        }
+    }
+}
+
+impl MockLavaTopBuilder {
+    /// API ключ, который сервер принимает в `X-Api-Key`.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// URL, на который отправляются вебхуки, и необязательный ключ для заголовка `X-Api-Key`.
+    pub fn webhook(mut self, url: Url, api_key: Option<String>) -> Self {
+        self.webhook = Some(WebhookTarget { url, api_key });
+        self
+    }
+
+    /// Размер страницы `GET /api/v2/products` (для проверки обхода по `nextPage`).
+    pub fn products_page_size(mut self, size: usize) -> Self {
+        self.products_page_size = size.max(1);
+        self
+    }
+
+    /// Добавляет продукт в каталог.
+    pub fn product(mut self, product: MockProduct) -> Self {
+        self.products.push(product);
+        self
+    }
+
+    /// Запускает сервер на `127.0.0.1` на свободном порту.
+    pub async fn start(self) -> Result<MockLavaTop, MockError> {
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let address = listener.local_addr()?;
+        let base_url = Url::parse(&format!("http://{address}/"))
+            .expect("Адрес локального сервера всегда является валидным URL");
+
+        let mut state = MockState::new(base_url.clone(), self.products_page_size);
+        state.products = self.products;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(state),
+            api_key: self.api_key,
+            base_url,
+            webhook: self.webhook,
+            http: reqwest::Client::new(),
+        });
+
+        let (shutdown, shutdown_signal) = oneshot::channel();
+        let app = routes::router(Arc::clone(&shared));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_signal.await;
+                })
+                .await;
+        });
+
+        Ok(MockLavaTop {
+            shared,
+            shutdown: Some(shutdown),
+        })
+    }
+}
+
+/// Общее состояние сервера и обработчиков.
+#[derive(Debug)]
+pub(crate) struct Shared {
+    state: Mutex<MockState>,
+    api_key: String,
+    base_url: Url,
+    webhook: Option<WebhookTarget>,
+    http: reqwest::Client,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Локальный мок-сервер gate.lava.top для тестов.
+///
+/// Реализует все эндпоинты, которые вызывает [`LavaTopClient`], хранит продукты, офферы,
+/// контракты и подписки в памяти и позволяет тестам управлять их состоянием. Если задан
+/// URL вебхука, переходы состояния отправляют соответствующие вебхуки.
+/// Сервер останавливается при удалении значения.
+///
+/// ```no_run
+/// # use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+/// # use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+/// # use lava_top_rs::models::invoice::InvoiceRequestDto;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let offer = MockOffer::new("Базовый").price(CurrencyDto::Rub, 990.0, Periodicity::OneTime);
+/// let offer_id = offer.id;
+/// let server = MockLavaTop::builder()
+///     .product(MockProduct::new("Курс", ProductType::Course).offer(offer))
+///     .start()
+///     .await?;
+///
+/// let client = server.client();
+/// let invoice = client
+///     .create_invoice_v2(&InvoiceRequestDto {
+///         email: "buyer@example.com".to_string(),
+///         offer_id,
+///         ..Default::default()
+///     })
+///     .await?;
+/// server.mark_invoice_paid(invoice.id).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MockLavaTop {
+    shared: Arc<Shared>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl Drop for MockLavaTop {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl MockLavaTop {
+    /// Построитель сервера.
+    pub fn builder() -> MockLavaTopBuilder {
+        MockLavaTopBuilder::default()
+    }
+
+    /// Запускает сервер с настройками по умолчанию и пустым каталогом.
+    pub async fn start() -> Result<Self, MockError> {
+        Self::builder().start().await
+    }
+
+    /// Базовый URL сервера.
+    pub fn base_url(&self) -> Url {
+        self.shared.base_url.clone()
+    }
+
+    /// API ключ, принимаемый сервером.
+    pub fn api_key(&self) -> &str {
+        &self.shared.api_key
+    }
+
+    /// Клиент, настроенный на этот сервер.
+    pub fn client(&self) -> LavaTopClient {
+        LavaTopClient::new(self.shared.api_key.clone(), Some(self.base_url()))
+            .expect("Параметры мок-сервера всегда валидны")
+    }
+
+    /// Добавляет продукт в каталог.
+    pub fn add_product(&self, product: MockProduct) {
+        self.shared.state().products.push(product);
+    }
+
+    /// Снимок продукта из каталога.
+    pub fn product(&self, id: Uuid) -> Option<MockProduct> {
+        self.shared
+            .state()
+            .products
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+    }
+
+    /// Снимок контракта.
+    pub fn contract(&self, id: Uuid) -> Option<MockContract> {
+        self.shared.state().contract(id).cloned()
+    }
+
+    /// Снимки всех контрактов в порядке создания.
+    pub fn contracts(&self) -> Vec<MockContract> {
+        self.shared.state().contracts.clone()
+    }
+
+    /// Задает ссылку, возвращаемую `GET /api/v1/donate`.
+    pub fn set_donate_url(&self, url: Url) {
+        self.shared.state().donate_url = url;
+    }
+
+    /// Отмечает контракт оплаченным и отправляет `payment_success`.
+    ///
+    /// Контракт с периодичностью подписки переходит в `subscription-active`.
+    pub async fn mark_invoice_paid(&self, id: Uuid) -> Result<(), MockError> {
+        let webhook = self.shared.state().mark_paid(id)?;
+        self.shared.deliver(webhook).await
+    }
+
+    /// Отмечает оплату неуспешной и отправляет `payment_failed`.
+    pub async fn mark_invoice_failed(
+        &self,
+        id: Uuid,
+        error_message: impl Into<String>,
+    ) -> Result<(), MockError> {
+        let webhook = self.shared.state().mark_failed(id, error_message.into())?;
+        self.shared.deliver(webhook).await
+    }
+
+    /// Проводит очередное списание по подписке и отправляет
+    /// `subscription_recurring_payment_success`. После неуспешного списания подписка
+    /// снова становится активной. Возвращает идентификатор нового контракта.
+    pub async fn charge_subscription(&self, parent_id: Uuid) -> Result<Uuid, MockError> {
+        let (id, webhook) = self.shared.state().charge(parent_id, None)?;
+        self.shared.deliver(webhook).await?;
+        Ok(id)
+    }
+
+    /// Проводит неуспешное списание по подписке и отправляет
+    /// `subscription_recurring_payment_failed`. Возвращает идентификатор нового контракта.
+    pub async fn fail_subscription_charge(
+        &self,
+        parent_id: Uuid,
+        error_message: impl Into<String>,
+    ) -> Result<Uuid, MockError> {
+        let (id, webhook) = self
+            .shared
+            .state()
+            .charge(parent_id, Some(error_message.into()))?;
+        self.shared.deliver(webhook).await?;
+        Ok(id)
+    }
+
+    /// Отменяет подписку (как `DELETE /api/v1/subscriptions`) и отправляет `subscription_cancelled`.
+    pub async fn cancel_subscription(&self, parent_id: Uuid) -> Result<(), MockError> {
+        let webhook = self.shared.state().cancel(parent_id)?;
+        self.shared.deliver(webhook).await
+    }
+}
+
+impl Shared {
+    /// Отправляет вебхук, если задан адрес доставки.
+    async fn deliver(&self, payload: serde_json::Value) -> Result<(), MockError> {
+        let Some(target) = &self.webhook else {
+            return Ok(());
+        };
+        let mut request = self.http.post(target.url.clone()).json(&payload);
+        if let Some(api_key) = &target.api_key {
+            let mut value = HeaderValue::from_str(api_key).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "неверный ключ вебхука")
+            })?;
+            value.set_sensitive(true);
+            request = request.header(crate::client::API_KEY_HEADER, value);
+        }
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+use crate::client::API_KEY_HEADER;
+use crate::models::common::{
+    ClientUtmDto, ContractStatusDto, CurrencyDto, FeedItemType, Periodicity, ProductType,
+};
+use crate::testing::Shared;
+use crate::testing::state::{
+    ApiFailure, InvoiceFilter, NewInvoice, OfferUpdate, PageRequest, ProductFilter, SaleFilter,
+};
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, RawQuery, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, patch, post};
+use axum::{Json, Router};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+impl IntoResponse for ApiFailure {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": self.message,
+            "details": Value::Null,
+            "timestamp": Utc::now(),
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiFailure {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiFailure::bad_request(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiFailure {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiFailure::bad_request(rejection.body_text())
+    }
+}
+
+/// Маршруты мок-сервера; все они требуют верный `X-Api-Key`.
+pub(crate) fn router(shared: Arc<Shared>) -> Router {
+    Router::new()
+        .route("/api/v1/feed", get(feed))
+        .route("/api/v1/invoice", post(create_invoice))
+        .route("/api/v2/invoice", post(create_invoice))
+        .route("/api/v1/invoices", get(list_invoices))
+        .route("/api/v1/invoices/{id}", get(get_invoice))
+        .route("/api/v1/sales/", get(list_sales))
+        .route("/api/v1/sales/{product_id}", get(list_product_sales))
+        .route(
+            "/api/v1/subscriptions",
+            axum::routing::delete(cancel_subscription),
+        )
+        .route("/api/v2/products", get(list_products))
+        .route("/api/v2/products/{id}", patch(update_product))
+        .route("/api/v1/donate", get(donate))
+        .route_layer(middleware::from_fn_with_state(shared.clone(), authorize))
+        .with_state(shared)
+}
+
+async fn authorize(State(shared): State<Arc<Shared>>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .is_some_and(|value| value.as_bytes() == shared.api_key.as_bytes());
+    if authorized {
+        next.run(request).await
+    } else {
+        ApiFailure {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Invalid API key".to_string(),
+        }
+        .into_response()
+    }
+}
+
+/// Разбирает список значений, разделенных запятыми.
+fn comma_separated<T: for<'a> From<&'a str>>(value: Option<String>) -> Option<Vec<T>> {
+    value.map(|value| {
+        value
+            .split(',')
+            .filter(|item| !item.is_empty())
+            .map(T::from)
+            .collect()
+    })
+}
+
+#[derive(Deserialize)]
+struct Paging {
+    page: Option<i64>,
+    size: Option<i64>,
+}
+
+impl Paging {
+    fn request(&self) -> Result<PageRequest, ApiFailure> {
+        let page = self.page.unwrap_or(1);
+        let size = self.size.unwrap_or(DEFAULT_PAGE_SIZE);
+        if page < 1 || !(1..=MAX_PAGE_SIZE).contains(&size) {
+            return Err(ApiFailure::bad_request(format!(
+                "Invalid paging: page={page}, size={size}"
+            )));
+        }
+        Ok(PageRequest { page, size })
+    }
+}
+
+// --- Продукты ---
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProductsQuery {
+    before_created_at: Option<DateTime<Utc>>,
+    content_categories: Option<FeedItemType>,
+    product_types: Option<ProductType>,
+    show_all_subscription_periods: Option<bool>,
+    cursor: Option<usize>,
+    page: Option<i64>,
+    size: Option<i64>,
+}
+
+impl ProductsQuery {
+    fn filter(&self) -> ProductFilter {
+        ProductFilter {
+            before_created_at: self.before_created_at,
+            only_posts: self.content_categories == Some(FeedItemType::Post),
+            product_type: self.product_types.clone(),
+            all_periods: self.show_all_subscription_periods.unwrap_or(false),
+        }
+    }
+}
+
+async fn feed(
+    State(shared): State<Arc<Shared>>,
+    query: Result<Query<ProductsQuery>, QueryRejection>,
+) -> Result<Json<Value>, ApiFailure> {
+    let Query(query) = query?;
+    let page = Paging {
+        page: query.page,
+        size: query.size,
+    }
+    .request()?;
+    Ok(Json(shared.state().feed(&query.filter(), page)))
+}
+
+async fn list_products(
+    State(shared): State<Arc<Shared>>,
+    RawQuery(raw_query): RawQuery,
+    query: Result<Query<ProductsQuery>, QueryRejection>,
+) -> Result<Json<Value>, ApiFailure> {
+    let Query(query) = query?;
+    let next_page = |offset: usize| {
+        let mut url = shared
+            .base_url
+            .join("api/v2/products")
+            .expect("Относительный путь всегда присоединяется к базовому URL");
+        let pairs: Vec<(String, String)> =
+            url::form_urlencoded::parse(raw_query.as_deref().unwrap_or_default().as_bytes())
+                .filter(|(key, _)| key != "cursor")
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect();
+        url.query_pairs_mut()
+            .extend_pairs(pairs)
+            .append_pair("cursor", &offset.to_string());
+        url
+    };
+    let page = shared
+        .state()
+        .list_products(&query.filter(), query.cursor.unwrap_or(0), next_page);
+    Ok(Json(page))
+}
+
+#[derive(Deserialize)]
+struct UpdatePriceBody {
+    amount: f64,
+    currency: CurrencyDto,
+}
+
+#[derive(Deserialize)]
+struct UpdateOfferBody {
+    id: Uuid,
+    prices: Option<Vec<UpdatePriceBody>>,
+    name: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProductUpdateBody {
+    #[serde(default)]
+    offers: Option<Vec<UpdateOfferBody>>,
+}
+
+async fn update_product(
+    State(shared): State<Arc<Shared>>,
+    Path(id): Path<Uuid>,
+    body: Result<Json<ProductUpdateBody>, JsonRejection>,
+) -> Result<Json<Value>, ApiFailure> {
+    let Json(body) = body?;
+    let updates = body
+        .offers
+        .unwrap_or_default()
+        .into_iter()
+        .map(|offer| OfferUpdate {
+            id: offer.id,
+            prices: offer.prices.map(|prices| {
+                prices
+                    .into_iter()
+                    .map(|price| (price.amount, price.currency))
+                    .collect()
+            }),
+            name: offer.name,
+            description: offer.description,
+        })
+        .collect();
+    shared.state().update_product(id, updates).map(Json)
+}
+
+// --- Контракты ---
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvoiceBody {
+    email: String,
+    offer_id: Uuid,
+    #[serde(default)]
+    currency: CurrencyDto,
+    periodicity: Option<Periodicity>,
+    client_utm: Option<ClientUtmDto>,
+}
+
+async fn create_invoice(
+    State(shared): State<Arc<Shared>>,
+    body: Result<Json<InvoiceBody>, JsonRejection>,
+) -> Result<Json<Value>, ApiFailure> {
+    let Json(body) = body?;
+    if !body.email.contains('@') {
+        return Err(ApiFailure::bad_request(format!(
+            "Invalid email: {}",
+            body.email
+        )));
+    }
+    shared
+        .state()
+        .create_invoice(NewInvoice {
+            email: body.email,
+            offer_id: body.offer_id,
+            currency: body.currency,
+            periodicity: body.periodicity,
+            client_utm: body.client_utm,
+        })
+        .map(Json)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvoicesQuery {
+    begin_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+    buyer_email: Option<String>,
+    product_name: Option<String>,
+    currencies: Option<String>,
+    invoice_types: Option<String>,
+    invoice_statuses: Option<String>,
+    page: Option<i64>,
+    size: Option<i64>,
+}
+
+async fn list_invoices(
+    State(shared): State<Arc<Shared>>,
+    query: Result<Query<InvoicesQuery>, QueryRejection>,
+) -> Result<Json<Value>, ApiFailure> {
+    let Query(query) = query?;
+    let page = Paging {
+        page: query.page,
+        size: query.size,
+    }
+    .request()?;
+    let filter = InvoiceFilter {
+        begin_date: query.begin_date,
+        end_date: query.end_date,
+        buyer_email: query.buyer_email,
+        product_name: query.product_name,
+        currencies: comma_separated(query.currencies),
+        invoice_types: comma_separated(query.invoice_types),
+        invoice_statuses: comma_separated(query.invoice_statuses),
+    };
+    Ok(Json(shared.state().list_invoices(&filter, page)))
+}
+
+async fn get_invoice(
+    State(shared): State<Arc<Shared>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiFailure> {
+    let state = shared.state();
+    let contract = state
+        .contract(id)
+        .ok_or_else(|| ApiFailure::not_found(format!("Invoice {id} not found")))?;
+    Ok(Json(state.invoice_json(contract)))
+}
+
+// --- Продажи ---
+
+async fn list_sales(
+    State(shared): State<Arc<Shared>>,
+    query: Result<Query<Paging>, QueryRejection>,
+) -> Result<Json<Value>, ApiFailure> {
+    let Query(paging) = query?;
+    Ok(Json(shared.state().list_sales(paging.request()?)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProductSalesQuery {
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+    currency: Option<CurrencyDto>,
+    status: Option<ContractStatusDto>,
+    search: Option<String>,
+    page: Option<i64>,
+    size: Option<i64>,
+}
+
+async fn list_product_sales(
+    State(shared): State<Arc<Shared>>,
+    Path(product_id): Path<Uuid>,
+    query: Result<Query<ProductSalesQuery>, QueryRejection>,
+) -> Result<Json<Value>, ApiFailure> {
+    let Query(query) = query?;
+    let page = Paging {
+        page: query.page,
+        size: query.size,
+    }
+    .request()?;
+    let filter = SaleFilter {
+        from_date: query.from_date,
+        to_date: query.to_date,
+        currency: query.currency,
+        status: query.status,
+        search: query.search,
+    };
+    shared
+        .state()
+        .list_product_sales(product_id, &filter, page)
+        .map(Json)
+}
+
+// --- Подписки и донаты ---
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelQuery {
+    contract_id: Uuid,
+    email: String,
+}
+
+async fn cancel_subscription(
+    State(shared): State<Arc<Shared>>,
+    query: Result<Query<CancelQuery>, QueryRejection>,
+) -> Result<StatusCode, ApiFailure> {
+    let Query(query) = query?;
+    let webhook = shared
+        .state()
+        .cancel_by_buyer(query.contract_id, &query.email)?;
+    // Как и настоящий сервис, отмена не зависит от успеха доставки вебхука.
+    let _ = shared.deliver(webhook).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn donate(State(shared): State<Arc<Shared>>) -> Json<Value> {
+    Json(json!({ "url": shared.state().donate_url }))
+}
@@ -0,0 +1,795 @@
+use crate::models::common::{
+    ClientUtmDto, ContractStatusDto, CurrencyDto, InvoiceStatus, InvoiceType, Periodicity,
+    PriceDto, SubscriptionStatus,
+};
+use crate::testing::{MockContract, MockError, MockOffer, MockProduct};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use url::Url;
+use uuid::Uuid;
+
+/// Ошибка обработки запроса к мок-серверу, отдаваемая клиенту как `ErrorResponse`.
+#[derive(Debug)]
+pub(crate) struct ApiFailure {
+    pub(crate) status: StatusCode,
+    pub(crate) message: String,
+}
+
+impl ApiFailure {
+    pub(crate) fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.into(),
+        }
+    }
+}
+
+/// Данные нового контракта из тела `POST /api/v*/invoice`.
+#[derive(Debug)]
+pub(crate) struct NewInvoice {
+    pub(crate) email: String,
+    pub(crate) offer_id: Uuid,
+    pub(crate) currency: CurrencyDto,
+    pub(crate) periodicity: Option<Periodicity>,
+    pub(crate) client_utm: Option<ClientUtmDto>,
+}
+
+/// Фильтры `GET /api/v1/invoices`.
+#[derive(Debug, Default)]
+pub(crate) struct InvoiceFilter {
+    pub(crate) begin_date: Option<DateTime<Utc>>,
+    pub(crate) end_date: Option<DateTime<Utc>>,
+    pub(crate) buyer_email: Option<String>,
+    pub(crate) product_name: Option<String>,
+    pub(crate) currencies: Option<Vec<CurrencyDto>>,
+    pub(crate) invoice_types: Option<Vec<InvoiceType>>,
+    pub(crate) invoice_statuses: Option<Vec<InvoiceStatus>>,
+}
+
+/// Фильтры `GET /api/v1/sales/{productId}`.
+#[derive(Debug, Default)]
+pub(crate) struct SaleFilter {
+    pub(crate) from_date: Option<chrono::NaiveDate>,
+    pub(crate) to_date: Option<chrono::NaiveDate>,
+    pub(crate) currency: Option<CurrencyDto>,
+    pub(crate) status: Option<ContractStatusDto>,
+    pub(crate) search: Option<String>,
+}
+
+/// Фильтры `GET /api/v2/products` и `GET /api/v1/feed`.
+#[derive(Debug, Default)]
+pub(crate) struct ProductFilter {
+    pub(crate) before_created_at: Option<DateTime<Utc>>,
+    pub(crate) only_posts: bool,
+    pub(crate) product_type: Option<crate::models::common::ProductType>,
+    pub(crate) all_periods: bool,
+}
+
+/// Изменения оффера из тела `PATCH /api/v2/products/{id}`.
+#[derive(Debug)]
+pub(crate) struct OfferUpdate {
+    pub(crate) id: Uuid,
+    pub(crate) prices: Option<Vec<(f64, CurrencyDto)>>,
+    pub(crate) name: Option<String>,
+    pub(crate) description: Option<String>,
+}
+
+/// Страница по номеру (1-based) и размеру.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PageRequest {
+    pub(crate) page: i64,
+    pub(crate) size: i64,
+}
+
+impl PageRequest {
+    /// Элементы страницы; страница за пределами списка (в том числе огромный номер) пуста.
+    fn slice<'a, T>(&self, items: &'a [T]) -> &'a [T] {
+        let start = (self.page - 1)
+            .checked_mul(self.size)
+            .and_then(|start| usize::try_from(start).ok());
+        let Some(start) = start else {
+            return &[];
+        };
+        let end = start.saturating_add(self.size as usize);
+        items.get(start..end.min(items.len())).unwrap_or(&[])
+    }
+
+    fn render(&self, items: Vec<Value>, total: usize) -> Value {
+        let total = total as i64;
+        json!({
+            "items": items,
+            "page": self.page,
+            "size": self.size,
+            "total": total,
+            "totalPages": (total + self.size - 1) / self.size,
+        })
+    }
+}
+
+/// Данные мок-сервера в памяти.
+#[derive(Debug)]
+pub(crate) struct MockState {
+    pub(crate) products: Vec<MockProduct>,
+    pub(crate) contracts: Vec<MockContract>,
+    pub(crate) donate_url: Url,
+    base_url: Url,
+    products_page_size: usize,
+}
+
+/// Конец периода подписки, начавшегося в `start`.
+fn period_end(periodicity: &Periodicity, start: DateTime<Utc>) -> DateTime<Utc> {
+    periodicity.period_end(start).unwrap_or(start)
+}
+
+/// Статус контракта в представлении `InvoiceResponseV2`.
+fn invoice_status(status: &ContractStatusDto) -> InvoiceStatus {
+    match status {
+        ContractStatusDto::New => InvoiceStatus::New,
+        ContractStatusDto::InProgress => InvoiceStatus::InProgress,
+        ContractStatusDto::Failed
+        | ContractStatusDto::Cancelled
+        | ContractStatusDto::SubscriptionFailed => InvoiceStatus::Failed,
+        _ => InvoiceStatus::Completed,
+    }
+}
+
+fn offer_json(offer: &MockOffer, all_periods: bool) -> Value {
+    let prices: Vec<&PriceDto> = offer
+        .prices
+        .iter()
+        .filter(|price| {
+            all_periods
+                || matches!(
+                    price.periodicity,
+                    None | Some(Periodicity::OneTime | Periodicity::Monthly)
+                )
+        })
+        .collect();
+    json!({
+        "id": offer.id,
+        "name": offer.name,
+        "description": offer.description,
+        "prices": prices,
+    })
+}
+
+impl MockState {
+    pub(crate) fn new(base_url: Url, products_page_size: usize) -> Self {
+        let donate_url = base_url
+            .join("donate")
+            .expect("Относительный путь всегда присоединяется к базовому URL");
+        Self {
+            products: Vec::new(),
+            contracts: Vec::new(),
+            donate_url,
+            base_url,
+            products_page_size,
+        }
+    }
+
+    pub(crate) fn contract(&self, id: Uuid) -> Option<&MockContract> {
+        self.contracts.iter().find(|c| c.id == id)
+    }
+
+    fn contract_mut(&mut self, id: Uuid) -> Result<&mut MockContract, MockError> {
+        self.contracts
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or(MockError::ContractNotFound(id))
+    }
+
+    fn find_offer(&self, offer_id: Uuid) -> Option<(&MockProduct, &MockOffer)> {
+        self.products.iter().find_map(|product| {
+            product
+                .offers
+                .iter()
+                .find(|offer| offer.id == offer_id)
+                .map(|offer| (product, offer))
+        })
+    }
+
+    fn product_title(&self, product_id: Uuid) -> Option<&str> {
+        self.products
+            .iter()
+            .find(|p| p.id == product_id)
+            .map(|p| p.title.as_str())
+    }
+
+    // --- Контракты ---
+
+    /// Создает контракт и возвращает тело `InvoicePaymentParamsResponse`.
+    pub(crate) fn create_invoice(&mut self, request: NewInvoice) -> Result<Value, ApiFailure> {
+        let (product, offer) = self.find_offer(request.offer_id).ok_or_else(|| {
+            ApiFailure::not_found(format!("Offer {} not found", request.offer_id))
+        })?;
+        let price = offer
+            .prices
+            .iter()
+            .filter(|price| price.currency == request.currency)
+            .find(|price| request.periodicity.is_none() || price.periodicity == request.periodicity)
+            .ok_or_else(|| {
+                ApiFailure::bad_request(format!(
+                    "Offer {} has no price in {} for the requested periodicity",
+                    offer.id, request.currency
+                ))
+            })?;
+        let periodicity = price.periodicity.clone().unwrap_or(Periodicity::OneTime);
+        let contract = MockContract {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            invoice_type: if periodicity == Periodicity::OneTime {
+                InvoiceType::OneTime
+            } else {
+                InvoiceType::Recurring
+            },
+            status: ContractStatusDto::New,
+            created_at: Utc::now(),
+            email: request.email,
+            product_id: product.id,
+            offer_id: offer.id,
+            currency: request.currency,
+            amount: price.amount.unwrap_or_default(),
+            periodicity,
+            subscription_status: None,
+            cancelled_at: None,
+            will_expire_at: None,
+            error_message: None,
+            client_utm: request.client_utm,
+        };
+        let payment_url = self
+            .base_url
+            .join(&format!("pay/{}", contract.id))
+            .expect("Относительный путь всегда присоединяется к базовому URL");
+        let response = json!({
+            "id": contract.id,
+            "status": contract.status,
+            "amountTotal": { "currency": contract.currency, "amount": contract.amount },
+            "paymentUrl": payment_url,
+        });
+        self.contracts.push(contract);
+        Ok(response)
+    }
+
+    /// Тело `InvoiceResponseV2` для контракта.
+    pub(crate) fn invoice_json(&self, contract: &MockContract) -> Value {
+        let offer_name = self
+            .find_offer(contract.offer_id)
+            .map(|(_, offer)| offer.name.clone());
+        let subscription_details = contract.subscription_status.as_ref().map(|_| {
+            json!({
+                "expiredAt": contract.will_expire_at,
+                "terminatedAt": Value::Null,
+                "cancelledAt": contract.cancelled_at,
+            })
+        });
+        json!({
+            "id": contract.id,
+            "type": contract.invoice_type,
+            "datetime": contract.created_at,
+            "status": invoice_status(&contract.status),
+            "receipt": { "amount": contract.amount, "currency": contract.currency, "fee": Value::Null },
+            "buyer": { "email": contract.email, "cardMask": Value::Null },
+            "product": { "name": self.product_title(contract.product_id), "offer": offer_name },
+            "parentInvoice": contract.parent_id.map(|id| json!({ "id": id })),
+            "subscriptionStatus": contract.subscription_status,
+            "subscriptionDetails": subscription_details,
+            "clientUtm": contract.client_utm,
+        })
+    }
+
+    /// Страница `GET /api/v1/invoices`.
+    pub(crate) fn list_invoices(&self, filter: &InvoiceFilter, page: PageRequest) -> Value {
+        let matching: Vec<&MockContract> = self
+            .contracts
+            .iter()
+            .filter(|c| filter.begin_date.is_none_or(|d| c.created_at >= d))
+            .filter(|c| filter.end_date.is_none_or(|d| c.created_at <= d))
+            .filter(|c| {
+                filter
+                    .buyer_email
+                    .as_ref()
+                    .is_none_or(|email| c.email.eq_ignore_ascii_case(email))
+            })
+            .filter(|c| {
+                filter.product_name.as_ref().is_none_or(|name| {
+                    self.product_title(c.product_id)
+                        .is_some_and(|title| title.contains(name.as_str()))
+                })
+            })
+            .filter(|c| {
+                filter
+                    .currencies
+                    .as_ref()
+                    .is_none_or(|list| list.contains(&c.currency))
+            })
+            .filter(|c| {
+                filter
+                    .invoice_types
+                    .as_ref()
+                    .is_none_or(|list| list.contains(&c.invoice_type))
+            })
+            .filter(|c| {
+                filter
+                    .invoice_statuses
+                    .as_ref()
+                    .is_none_or(|list| list.contains(&invoice_status(&c.status)))
+            })
+            .collect();
+        let items = page
+            .slice(&matching)
+            .iter()
+            .map(|c| self.invoice_json(c))
+            .collect();
+        page.render(items, matching.len())
+    }
+
+    // --- Продажи ---
+
+    fn is_sale(contract: &MockContract) -> bool {
+        invoice_status(&contract.status) == InvoiceStatus::Completed
+    }
+
+    /// Страница `GET /api/v1/sales/`: продажи, сгруппированные по продуктам и валютам.
+    pub(crate) fn list_sales(&self, page: PageRequest) -> Value {
+        let items: Vec<Value> = page
+            .slice(&self.products)
+            .iter()
+            .map(|product| {
+                let mut sales: Vec<(CurrencyDto, i64, f64)> = Vec::new();
+                for contract in self
+                    .contracts
+                    .iter()
+                    .filter(|c| c.product_id == product.id && Self::is_sale(c))
+                {
+                    match sales
+                        .iter_mut()
+                        .find(|(cur, _, _)| *cur == contract.currency)
+                    {
+                        Some((_, count, total)) => {
+                            *count += 1;
+                            *total += contract.amount;
+                        }
+                        None => sales.push((contract.currency.clone(), 1, contract.amount)),
+                    }
+                }
+                let sales: Vec<Value> = sales
+                    .into_iter()
+                    .map(|(currency, count, total)| {
+                        json!({ "currency": currency, "count": count, "amountTotal": total })
+                    })
+                    .collect();
+                json!({
+                    "productId": product.id,
+                    "title": product.title,
+                    "status": "PUBLISHED",
+                    "sales": sales,
+                })
+            })
+            .collect();
+        page.render(items, self.products.len())
+    }
+
+    /// Страница `GET /api/v1/sales/{productId}`.
+    pub(crate) fn list_product_sales(
+        &self,
+        product_id: Uuid,
+        filter: &SaleFilter,
+        page: PageRequest,
+    ) -> Result<Value, ApiFailure> {
+        if !self.products.iter().any(|p| p.id == product_id) {
+            return Err(ApiFailure::not_found(format!(
+                "Product {product_id} not found"
+            )));
+        }
+        let matching: Vec<&MockContract> = self
+            .contracts
+            .iter()
+            .filter(|c| c.product_id == product_id)
+            .filter(|c| {
+                filter
+                    .from_date
+                    .is_none_or(|d| c.created_at.date_naive() >= d)
+            })
+            .filter(|c| {
+                filter
+                    .to_date
+                    .is_none_or(|d| c.created_at.date_naive() <= d)
+            })
+            .filter(|c| {
+                filter
+                    .currency
+                    .as_ref()
+                    .is_none_or(|cur| c.currency == *cur)
+            })
+            .filter(|c| filter.status.as_ref().is_none_or(|s| c.status == *s))
+            .filter(|c| {
+                filter
+                    .search
+                    .as_ref()
+                    .is_none_or(|text| c.email.contains(text.as_str()))
+            })
+            .collect();
+        let items = page
+            .slice(&matching)
+            .iter()
+            .map(|c| {
+                json!({
+                    "id": c.id,
+                    "createdAt": c.created_at,
+                    "status": c.status,
+                    "amountTotal": { "currency": c.currency, "amount": c.amount },
+                    "buyer": { "email": c.email },
+                })
+            })
+            .collect();
+        Ok(page.render(items, matching.len()))
+    }
+
+    // --- Продукты ---
+
+    fn visible_products(&self, filter: &ProductFilter) -> Vec<&MockProduct> {
+        if filter.only_posts {
+            return Vec::new();
+        }
+        self.products
+            .iter()
+            .filter(|p| filter.before_created_at.is_none_or(|d| p.created_at < d))
+            .filter(|p| {
+                filter
+                    .product_type
+                    .as_ref()
+                    .is_none_or(|t| p.product_type == *t)
+            })
+            .collect()
+    }
+
+    fn product_json(product: &MockProduct, all_periods: bool) -> Value {
+        json!({
+            "id": product.id,
+            "title": product.title,
+            "description": product.description,
+            "type": product.product_type,
+            "offers": product
+                .offers
+                .iter()
+                .map(|offer| offer_json(offer, all_periods))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Страница `GET /api/v2/products`; `next_page` строит ссылку на следующую страницу по смещению.
+    pub(crate) fn list_products(
+        &self,
+        filter: &ProductFilter,
+        offset: usize,
+        next_page: impl FnOnce(usize) -> Url,
+    ) -> Value {
+        let products = self.visible_products(filter);
+        let end = offset.saturating_add(self.products_page_size);
+        let items: Vec<Value> = products
+            .get(offset..end.min(products.len()))
+            .unwrap_or(&[])
+            .iter()
+            .map(|product| {
+                json!({
+                    "type": "PRODUCT",
+                    "data": Self::product_json(product, filter.all_periods),
+                })
+            })
+            .collect();
+        let next = (end < products.len()).then(|| next_page(end));
+        json!({ "items": items, "nextPage": next })
+    }
+
+    /// Страница устаревшего `GET /api/v1/feed`.
+    pub(crate) fn feed(&self, filter: &ProductFilter, page: PageRequest) -> Value {
+        let products = self.visible_products(filter);
+        let items: Vec<Value> = page
+            .slice(&products)
+            .iter()
+            .map(|product| {
+                let offers: Vec<Value> = product
+                    .offers
+                    .iter()
+                    .map(|offer| {
+                        let has_sales = self.contracts.iter().any(|c| c.offer_id == offer.id);
+                        let mut value = offer_json(offer, true);
+                        value["availablePosts"] = json!([]);
+                        value["canBeDeleted"] = json!(!has_sales);
+                        value["reasons"] = if has_sales {
+                            json!(["HAS_SALES"])
+                        } else {
+                            json!([])
+                        };
+                        value
+                    })
+                    .collect();
+                json!({
+                    "type": "PRODUCT",
+                    "data": {
+                        "id": product.id,
+                        "title": product.title,
+                        "updatedAt": product.created_at,
+                        "status": "PUBLISHED",
+                        "moderationStatus": "APPROVED",
+                        "type": product.product_type,
+                        "offers": offers,
+                    },
+                })
+            })
+            .collect();
+        json!({
+            "items": items,
+            "page": page.page,
+            "size": page.size,
+            "total": products.len(),
+        })
+    }
+
+    /// Применяет `PATCH /api/v2/products/{id}` и возвращает обновленный продукт.
+    pub(crate) fn update_product(
+        &mut self,
+        product_id: Uuid,
+        updates: Vec<OfferUpdate>,
+    ) -> Result<Value, ApiFailure> {
+        let product = self
+            .products
+            .iter_mut()
+            .find(|p| p.id == product_id)
+            .ok_or_else(|| ApiFailure::not_found(format!("Product {product_id} not found")))?;
+        if let Some(update) = updates
+            .iter()
+            .find(|u| !product.offers.iter().any(|o| o.id == u.id))
+        {
+            return Err(ApiFailure::bad_request(format!(
+                "Offer {} does not belong to product {product_id}",
+                update.id
+            )));
+        }
+        for update in updates {
+            let offer = product
+                .offers
+                .iter_mut()
+                .find(|o| o.id == update.id)
+                .expect("Принадлежность офферов проверена выше");
+            if let Some(name) = update.name {
+                offer.name = name;
+            }
+            if let Some(description) = update.description {
+                offer.description = Some(description);
+            }
+            if let Some(prices) = update.prices {
+                let default_periodicity = offer.prices.first().and_then(|p| p.periodicity.clone());
+                offer.prices = prices
+                    .into_iter()
+                    .map(|(amount, currency)| {
+                        let periodicity = offer
+                            .prices
+                            .iter()
+                            .find(|p| p.currency == currency)
+                            .map_or(default_periodicity.clone(), |p| p.periodicity.clone());
+                        PriceDto {
+                            amount: Some(amount),
+                            currency,
+                            periodicity,
+                        }
+                    })
+                    .collect();
+            }
+        }
+        Ok(Self::product_json(product, true))
+    }
+
+    // --- Переходы состояния ---
+
+    fn webhook(
+        &self,
+        event_type: &str,
+        contract: &MockContract,
+        parent_id: Option<Uuid>,
+        status: &ContractStatusDto,
+    ) -> Value {
+        json!({
+            "eventType": event_type,
+            "product": { "id": contract.product_id, "title": self.product_title(contract.product_id) },
+            "contractId": contract.id,
+            "parentContractId": parent_id,
+            "buyer": { "email": contract.email },
+            "amount": contract.amount,
+            "currency": contract.currency,
+            "status": status,
+            "timestamp": Utc::now(),
+            "clientUtm": contract.client_utm,
+            "errorMessage": contract.error_message,
+        })
+    }
+
+    fn ensure_status(
+        contract: &MockContract,
+        allowed: &[ContractStatusDto],
+        action: &'static str,
+    ) -> Result<(), MockError> {
+        if allowed.contains(&contract.status) {
+            Ok(())
+        } else {
+            Err(MockError::InvalidState {
+                id: contract.id,
+                status: contract.status.clone(),
+                action,
+            })
+        }
+    }
+
+    pub(crate) fn mark_paid(&mut self, id: Uuid) -> Result<Value, MockError> {
+        let contract = self.contract_mut(id)?;
+        Self::ensure_status(
+            contract,
+            &[ContractStatusDto::New, ContractStatusDto::InProgress],
+            "mark_invoice_paid",
+        )?;
+        if contract.invoice_type == InvoiceType::Recurring {
+            contract.status = ContractStatusDto::SubscriptionActive;
+            contract.subscription_status = Some(SubscriptionStatus::Active);
+            contract.will_expire_at = Some(period_end(&contract.periodicity, Utc::now()));
+        } else {
+            contract.status = ContractStatusDto::Completed;
+        }
+        let contract = contract.clone();
+        Ok(self.webhook("payment_success", &contract, None, &contract.status))
+    }
+
+    pub(crate) fn mark_failed(
+        &mut self,
+        id: Uuid,
+        error_message: String,
+    ) -> Result<Value, MockError> {
+        let contract = self.contract_mut(id)?;
+        Self::ensure_status(
+            contract,
+            &[ContractStatusDto::New, ContractStatusDto::InProgress],
+            "mark_invoice_failed",
+        )?;
+        contract.status = ContractStatusDto::Failed;
+        contract.error_message = Some(error_message);
+        let contract = contract.clone();
+        Ok(self.webhook("payment_failed", &contract, None, &contract.status))
+    }
+
+    pub(crate) fn charge(
+        &mut self,
+        parent_id: Uuid,
+        error_message: Option<String>,
+    ) -> Result<(Uuid, Value), MockError> {
+        let parent = self.contract_mut(parent_id)?;
+        Self::ensure_status(
+            parent,
+            &[
+                ContractStatusDto::SubscriptionActive,
+                ContractStatusDto::SubscriptionFailed,
+            ],
+            "charge_subscription",
+        )?;
+        let failed = error_message.is_some();
+        if failed {
+            parent.status = ContractStatusDto::SubscriptionFailed;
+            parent.subscription_status = Some(SubscriptionStatus::Failed);
+        } else {
+            // Успешный повтор списания возобновляет подписку
+            parent.status = ContractStatusDto::SubscriptionActive;
+            parent.subscription_status = Some(SubscriptionStatus::Active);
+            parent.will_expire_at = Some(period_end(
+                &parent.periodicity,
+                parent.will_expire_at.unwrap_or_else(Utc::now),
+            ));
+        }
+        let charge = MockContract {
+            id: Uuid::new_v4(),
+            parent_id: Some(parent_id),
+            status: if failed {
+                ContractStatusDto::Failed
+            } else {
+                ContractStatusDto::Completed
+            },
+            created_at: Utc::now(),
+            subscription_status: None,
+            cancelled_at: None,
+            will_expire_at: None,
+            error_message,
+            ..parent.clone()
+        };
+        let (event_type, status) = if failed {
+            (
+                "subscription_recurring_payment_failed",
+                ContractStatusDto::SubscriptionFailed,
+            )
+        } else {
+            (
+                "subscription_recurring_payment_success",
+                ContractStatusDto::SubscriptionActive,
+            )
+        };
+        let webhook = self.webhook(event_type, &charge, Some(parent_id), &status);
+        let id = charge.id;
+        self.contracts.push(charge);
+        Ok((id, webhook))
+    }
+
+    pub(crate) fn cancel(&mut self, parent_id: Uuid) -> Result<Value, MockError> {
+        let parent = self.contract_mut(parent_id)?;
+        Self::ensure_status(
+            parent,
+            &[
+                ContractStatusDto::SubscriptionActive,
+                ContractStatusDto::SubscriptionFailed,
+            ],
+            "cancel_subscription",
+        )?;
+        let now = Utc::now();
+        parent.status = ContractStatusDto::SubscriptionCancelled;
+        parent.subscription_status = Some(SubscriptionStatus::Cancelled);
+        parent.cancelled_at = Some(now);
+        let will_expire_at = *parent.will_expire_at.get_or_insert(now);
+        let parent = parent.clone();
+        let mut webhook = self.webhook(
+            "subscription_cancelled",
+            &parent,
+            parent.parent_id,
+            &parent.status,
+        );
+        webhook["cancelledAt"] = json!(now);
+        webhook["willExpireAt"] = json!(will_expire_at);
+        Ok(webhook)
+    }
+
+    /// Отмена через `DELETE /api/v1/subscriptions`: проверяет контракт и почту покупателя.
+    pub(crate) fn cancel_by_buyer(
+        &mut self,
+        contract_id: Uuid,
+        email: &str,
+    ) -> Result<Value, ApiFailure> {
+        let contract = self
+            .contract(contract_id)
+            .ok_or_else(|| ApiFailure::not_found(format!("Contract {contract_id} not found")))?;
+        if !contract.email.eq_ignore_ascii_case(email) {
+            return Err(ApiFailure::not_found(format!(
+                "Contract {contract_id} not found for {email}"
+            )));
+        }
+        self.cancel(contract_id)
+            .map_err(|err| ApiFailure::bad_request(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_slice() {
+        let items: Vec<u32> = (1..=5).collect();
+        let page = |page, size| PageRequest { page, size }.slice(&items).to_vec();
+        assert_eq!(page(1, 2), [1, 2]);
+        assert_eq!(page(3, 2), [5]);
+        assert_eq!(page(4, 2), Vec::<u32>::new());
+        assert_eq!(page(1, 100), [1, 2, 3, 4, 5]);
+        assert_eq!(page(i64::MAX, 100), Vec::<u32>::new());
+        assert_eq!(page(i64::MAX / 2, 3), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn page_render() {
+        let page = PageRequest { page: 2, size: 2 }.render(vec![json!(3), json!(4)], 5);
+        assert_eq!(
+            page,
+            json!({ "items": [3, 4], "page": 2, "size": 2, "total": 5, "totalPages": 3 })
+        );
+    }
+}
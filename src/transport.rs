@@ -0,0 +1,128 @@
+use crate::error::{BoxError, LavaTopError};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use reqwest::Client as ReqwestClient;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower::util::BoxCloneSyncService;
+use tower::{Layer, Service};
+
+/// Исходящий запрос к API: тело уже сериализовано в JSON.
+pub type HttpRequest = http::Request<Bytes>;
+
+/// Ответ API с полностью прочитанным телом.
+pub type HttpResponse = http::Response<Bytes>;
+
+/// Транспорт клиента: любой клонируемый `tower::Service` над [`HttpRequest`].
+///
+/// Ошибки сервиса приводятся к [`BoxError`], как принято в экосистеме tower. Клиент
+/// восстанавливает из них [`LavaTopError`] (например, [`LavaTopError::Reqwest`]).
+pub type BoxTransport = BoxCloneSyncService<HttpRequest, HttpResponse, BoxError>;
+
+/// Нижний уровень стека: отправляет запрос через reqwest и читает тело ответа целиком.
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: ReqwestClient,
+}
+
+impl ReqwestTransport {
+    /// Транспорт поверх готового клиента reqwest.
+    pub fn new(client: ReqwestClient) -> Self {
+        Self { client }
+    }
+}
+
+impl Service<HttpRequest> for ReqwestTransport {
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let client = self.client.clone();
+        Box::pin(async move {
+            let request = reqwest::Request::try_from(request.map(reqwest::Body::from))?;
+            let response = client.execute(request).await?;
+
+            let mut builder = http::Response::builder()
+                .status(response.status())
+                .version(response.version());
+            if let Some(headers) = builder.headers_mut() {
+                *headers = response.headers().clone();
+            }
+            let body = response.bytes().await?;
+            Ok(builder.body(body)?)
+        })
+    }
+}
+
+/// Слой, ограничивающий время одной попытки запроса.
+///
+/// По истечении времени возвращает [`LavaTopError::Timeout`].
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutLayer {
+    timeout: Duration,
+}
+
+impl TimeoutLayer {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<S> Layer<S> for TimeoutLayer {
+    type Service = Timeout<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Timeout {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Сервис, созданный [`TimeoutLayer`].
+#[derive(Debug, Clone)]
+pub struct Timeout<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+impl<S> Service<HttpRequest> for Timeout<S>
+where
+    S: Service<HttpRequest, Response = HttpResponse, Error = BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = HttpResponse;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<HttpResponse, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        let timeout = self.timeout;
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            match crate::runtime::timeout(timeout, response).await {
+                Ok(result) => result,
+                Err(_) => Err(LavaTopError::Timeout(timeout).into()),
+            }
+        })
+    }
+}
+
+/// Копия запроса для повторной отправки (тело буферизовано, поэтому копирование дешевое).
+pub(crate) fn clone_request(request: &HttpRequest) -> HttpRequest {
+    let mut copy = http::Request::new(request.body().clone());
+    *copy.method_mut() = request.method().clone();
+    *copy.uri_mut() = request.uri().clone();
+    *copy.version_mut() = request.version();
+    *copy.headers_mut() = request.headers().clone();
+    *copy.extensions_mut() = request.extensions().clone();
+    copy
+}
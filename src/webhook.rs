@@ -0,0 +1,126 @@
+use crate::error::{BoxError, LavaTopError, WebhookAuthError};
+use crate::models::webhook::{PurchaseWebhookLog, WebhookEvent};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "hyper")]
+pub mod hyper;
+mod verifier;
+
+pub use verifier::WebhookVerifier;
+
+/// Максимальный размер тела вебхука по умолчанию (64 КиБ).
+pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Проверка подлинности входящего вебхука по заголовкам запроса.
+///
+/// Встроенная реализация — [`WebhookVerifier`]; также реализован для замыканий `Fn(&HeaderMap) -> bool`.
+pub trait WebhookAuthenticator: Send + Sync {
+    /// Возвращает [`LavaTopError::WebhookAuth`], если запрос не прошел проверку.
+    fn authenticate(&self, headers: &HeaderMap) -> Result<(), LavaTopError>;
+}
+
+impl<F> WebhookAuthenticator for F
+where
+    F: Fn(&HeaderMap) -> bool + Send + Sync,
+{
+    fn authenticate(&self, headers: &HeaderMap) -> Result<(), LavaTopError> {
+        if self(headers) {
+            Ok(())
+        } else {
+            Err(WebhookAuthError::Rejected.into())
+        }
+    }
+}
+
+/// Обработчик событий вебхука, реализуемый приложением.
+///
+/// Реализован для замыканий вида `Fn(WebhookEvent) -> impl Future<Output = Result<(), BoxError>>`.
+pub trait WebhookHandler: Send + Sync {
+    /// Обрабатывает событие. Ошибка приводит к ответу 500, и Lava Top повторит доставку.
+    fn handle(&self, event: WebhookEvent) -> impl Future<Output = Result<(), BoxError>> + Send;
+}
+
+impl<F, Fut> WebhookHandler for F
+where
+    F: Fn(WebhookEvent) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), BoxError>> + Send,
+{
+    fn handle(&self, event: WebhookEvent) -> impl Future<Output = Result<(), BoxError>> + Send {
+        self(event)
+    }
+}
+
+/// Приемник вебхуков, не зависящий от HTTP фреймворка.
+///
+/// Принимает сырые заголовки и тело запроса, проверяет подлинность через [`WebhookAuthenticator`],
+/// разбирает тело и передает событие [`WebhookHandler`]. Готовые адаптеры для axum и hyper
+/// доступны при включении одноименных features.
+#[derive(Debug, Clone)]
+pub struct WebhookReceiver<A, H> {
+    authenticator: A,
+    handler: H,
+    max_body_size: usize,
+}
+
+impl<A, H> WebhookReceiver<A, H>
+where
+    A: WebhookAuthenticator,
+    H: WebhookHandler,
+{
+    /// Создает приемник с заданными проверкой подлинности и обработчиком.
+    pub fn new(authenticator: A, handler: H) -> Self {
+        Self {
+            authenticator,
+            handler,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Максимальный допустимый размер тела запроса в байтах.
+    pub fn max_body_size(mut self, limit: usize) -> Self {
+        self.max_body_size = limit;
+        self
+    }
+
+    /// Текущий лимит размера тела запроса.
+    pub fn body_limit(&self) -> usize {
+        self.max_body_size
+    }
+
+    /// Проверяет подлинность запроса и разбирает тело в типизированное событие, не вызывая обработчик.
+    ///
+    /// Если для типа события отсутствует обязательное поле, возвращается [`LavaTopError::MissingField`].
+    pub fn parse(&self, headers: &HeaderMap, body: &[u8]) -> Result<WebhookEvent, LavaTopError> {
+        self.authenticator.authenticate(headers)?;
+        if body.len() > self.max_body_size {
+            return Err(LavaTopError::WebhookPayloadTooLarge(self.max_body_size));
+        }
+        let log: PurchaseWebhookLog = serde_json::from_slice(body)?;
+        WebhookEvent::try_from(log)
+    }
+
+    /// Полный цикл обработки: проверка подлинности, разбор и вызов обработчика.
+    pub async fn receive(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), LavaTopError> {
+        let event = self.parse(headers, body)?;
+        self.handler
+            .handle(event)
+            .await
+            .map_err(LavaTopError::WebhookHandler)
+    }
+}
+
+/// HTTP статус ответа для результата [`WebhookReceiver::receive`].
+///
+/// Используется адаптерами; пригодится и при интеграции с другими фреймворками.
+pub fn response_status(result: &Result<(), LavaTopError>) -> StatusCode {
+    match result {
+        Ok(()) => StatusCode::OK,
+        Err(LavaTopError::WebhookAuth(_)) => StatusCode::UNAUTHORIZED,
+        Err(LavaTopError::WebhookPayloadTooLarge(_)) => StatusCode::PAYLOAD_TOO_LARGE,
+        Err(LavaTopError::Serde(_) | LavaTopError::MissingField(_)) => StatusCode::BAD_REQUEST,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
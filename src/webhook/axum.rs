@@ -0,0 +1,40 @@
+use crate::webhook::{WebhookAuthenticator, WebhookHandler, WebhookReceiver, response_status};
+use ::axum::Router;
+use ::axum::extract::{DefaultBodyLimit, State};
+use ::axum::http::{HeaderMap, StatusCode};
+use ::axum::routing::post;
+use bytes::Bytes;
+use std::sync::Arc;
+
+impl<A, H> WebhookReceiver<A, H>
+where
+    A: WebhookAuthenticator + 'static,
+    H: WebhookHandler + 'static,
+{
+    /// Создает axum `Router` с единственным маршрутом `POST path`.
+    pub fn into_axum_router<S>(self, path: &str) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let limit = self.body_limit();
+        Router::new()
+            .route(path, post(handle::<A, H>))
+            .layer(DefaultBodyLimit::max(limit))
+            .with_state(Arc::new(self))
+    }
+}
+
+/// Обработчик axum для подключения к собственному `Router`.
+///
+/// Состояние маршрута — `Arc<WebhookReceiver<A, H>>`.
+pub async fn handle<A, H>(
+    State(receiver): State<Arc<WebhookReceiver<A, H>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode
+where
+    A: WebhookAuthenticator,
+    H: WebhookHandler,
+{
+    response_status(&receiver.receive(&headers, &body).await)
+}
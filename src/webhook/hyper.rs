@@ -0,0 +1,76 @@
+use crate::webhook::{WebhookAuthenticator, WebhookHandler, WebhookReceiver, response_status};
+use ::hyper::body::Incoming;
+use ::hyper::service::Service;
+use ::hyper::{Method, Request, Response, StatusCode};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full, Limited};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Сервис hyper, принимающий вебхуки на любом пути.
+///
+/// Запросы с методом, отличным от POST, получают ответ 405.
+#[derive(Debug)]
+pub struct WebhookService<A, H> {
+    receiver: Arc<WebhookReceiver<A, H>>,
+}
+
+impl<A, H> Clone for WebhookService<A, H> {
+    fn clone(&self) -> Self {
+        Self {
+            receiver: Arc::clone(&self.receiver),
+        }
+    }
+}
+
+impl<A, H> WebhookService<A, H> {
+    /// Оборачивает общий приемник.
+    pub fn new(receiver: Arc<WebhookReceiver<A, H>>) -> Self {
+        Self { receiver }
+    }
+}
+
+impl<A, H> WebhookReceiver<A, H>
+where
+    A: WebhookAuthenticator + 'static,
+    H: WebhookHandler + 'static,
+{
+    /// Создает сервис hyper на основе приемника.
+    pub fn into_hyper_service(self) -> WebhookService<A, H> {
+        WebhookService::new(Arc::new(self))
+    }
+}
+
+impl<A, H> Service<Request<Incoming>> for WebhookService<A, H>
+where
+    A: WebhookAuthenticator + 'static,
+    H: WebhookHandler + 'static,
+{
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn call(&self, request: Request<Incoming>) -> Self::Future {
+        let receiver = Arc::clone(&self.receiver);
+        Box::pin(async move {
+            if request.method() != Method::POST {
+                return Ok(empty_response(StatusCode::METHOD_NOT_ALLOWED));
+            }
+            let (parts, body) = request.into_parts();
+            let body = match Limited::new(body, receiver.body_limit()).collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(_) => return Ok(empty_response(StatusCode::PAYLOAD_TOO_LARGE)),
+            };
+            let result = receiver.receive(&parts.headers, &body).await;
+            Ok(empty_response(response_status(&result)))
+        })
+    }
+}
+
+fn empty_response(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::new()));
+    *response.status_mut() = status;
+    response
+}
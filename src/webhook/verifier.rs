@@ -0,0 +1,245 @@
+use crate::error::{LavaTopError, WebhookAuthError};
+use crate::secret::SecretString;
+use crate::webhook::WebhookAuthenticator;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroizing;
+
+/// Проверка подлинности вебхуков по API ключу или HTTP Basic авторизации.
+///
+/// Секреты сравниваются за постоянное время. Можно задать несколько допустимых секретов
+/// каждого вида, чтобы менять их без простоя: запрос принимается, если совпал любой из них.
+/// Секреты хранятся как [`SecretString`] и не выводятся в `Debug`.
+///
+/// ```
+/// # use lava_top_rs::webhook::WebhookVerifier;
+/// let verifier = WebhookVerifier::new()
+///     .with_api_key("new-key")
+///     .with_api_key("old-key") // удалить после смены ключа в кабинете Lava Top
+///     .with_basic_auth("lava", "password");
+/// ```
+#[derive(Debug, Clone)]
+pub struct WebhookVerifier {
+    header_name: HeaderName,
+    api_keys: Vec<SecretString>,
+    basic_credentials: Vec<SecretString>,
+}
+
+impl Default for WebhookVerifier {
+    fn default() -> Self {
+        Self {
+            header_name: HeaderName::from_static("x-api-key"),
+            api_keys: Vec::new(),
+            basic_credentials: Vec::new(),
+        }
+    }
+}
+
+impl WebhookVerifier {
+    /// Создает проверку без допустимых секретов. Пока секреты не добавлены, все запросы отклоняются.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Проверка по одному API ключу в заголовке `X-Api-Key`.
+    pub fn api_key(key: impl Into<SecretString>) -> Self {
+        Self::new().with_api_key(key)
+    }
+
+    /// Проверка по одной паре логин/пароль HTTP Basic.
+    pub fn basic_auth(username: impl AsRef<str>, password: impl Into<SecretString>) -> Self {
+        Self::new().with_basic_auth(username, password)
+    }
+
+    /// Добавляет допустимый API ключ.
+    pub fn with_api_key(mut self, key: impl Into<SecretString>) -> Self {
+        self.api_keys.push(key.into());
+        self
+    }
+
+    /// Добавляет допустимую пару логин/пароль HTTP Basic.
+    pub fn with_basic_auth(
+        mut self,
+        username: impl AsRef<str>,
+        password: impl Into<SecretString>,
+    ) -> Self {
+        let password = password.into();
+        let credentials = Zeroizing::new(format!(
+            "{}:{}",
+            username.as_ref(),
+            password.expose_secret()
+        ));
+        self.basic_credentials
+            .push(SecretString::from(BASE64.encode(credentials.as_bytes())));
+        self
+    }
+
+    /// Заголовок, в котором Lava Top передает API ключ. По умолчанию `X-Api-Key`.
+    pub fn header_name(mut self, name: HeaderName) -> Self {
+        self.header_name = name;
+        self
+    }
+
+    /// Проверяет заголовки входящего запроса.
+    pub fn verify(&self, headers: &HeaderMap) -> Result<(), LavaTopError> {
+        let mut presented = false;
+        let mut matched = Choice::from(0);
+
+        if !self.api_keys.is_empty()
+            && let Some(value) = headers.get(&self.header_name)
+        {
+            presented = true;
+            matched |= any_matches(&self.api_keys, value.as_bytes());
+        }
+
+        if !self.basic_credentials.is_empty()
+            && let Some(value) = headers.get(AUTHORIZATION)
+        {
+            presented = true;
+            let token = value
+                .to_str()
+                .ok()
+                .and_then(|v| v.split_once(' '))
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("basic"))
+                .map(|(_, token)| token.trim());
+            match token {
+                Some(token) => matched |= any_matches(&self.basic_credentials, token.as_bytes()),
+                None => return Err(WebhookAuthError::MalformedCredentials.into()),
+            }
+        }
+
+        if !presented {
+            return Err(WebhookAuthError::MissingCredentials.into());
+        }
+        if bool::from(matched) {
+            Ok(())
+        } else {
+            Err(WebhookAuthError::InvalidCredentials.into())
+        }
+    }
+}
+
+impl WebhookAuthenticator for WebhookVerifier {
+    fn authenticate(&self, headers: &HeaderMap) -> Result<(), LavaTopError> {
+        self.verify(headers)
+    }
+}
+
+/// Сравнивает значение со всеми секретами, не прерываясь на первом совпадении.
+fn any_matches(secrets: &[SecretString], value: &[u8]) -> Choice {
+    secrets.iter().fold(Choice::from(0), |acc, secret| {
+        acc | secret.expose_secret().as_bytes().ct_eq(value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers
+    }
+
+    fn auth_error(result: Result<(), LavaTopError>) -> WebhookAuthError {
+        match result {
+            Err(LavaTopError::WebhookAuth(error)) => error,
+            other => panic!("ожидалась ошибка авторизации, получено {other:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_any_configured_api_key() {
+        let verifier = WebhookVerifier::new()
+            .with_api_key("new-key")
+            .with_api_key("old-key");
+        assert!(verifier.verify(&headers("x-api-key", "new-key")).is_ok());
+        assert!(verifier.verify(&headers("X-Api-Key", "old-key")).is_ok());
+        assert!(matches!(
+            auth_error(verifier.verify(&headers("x-api-key", "other"))),
+            WebhookAuthError::InvalidCredentials
+        ));
+    }
+
+    #[test]
+    fn custom_header_name() {
+        let verifier =
+            WebhookVerifier::api_key("key").header_name(HeaderName::from_static("x-lava-key"));
+        assert!(verifier.verify(&headers("x-lava-key", "key")).is_ok());
+        assert!(matches!(
+            auth_error(verifier.verify(&headers("x-api-key", "key"))),
+            WebhookAuthError::MissingCredentials
+        ));
+    }
+
+    #[test]
+    fn basic_auth() {
+        let verifier = WebhookVerifier::basic_auth("lava", "password");
+        let token = BASE64.encode("lava:password");
+        assert!(
+            verifier
+                .verify(&headers("authorization", &format!("Basic {token}")))
+                .is_ok()
+        );
+        assert!(
+            verifier
+                .verify(&headers("authorization", &format!("basic  {token} ")))
+                .is_ok()
+        );
+        let wrong = BASE64.encode("lava:wrong");
+        assert!(matches!(
+            auth_error(verifier.verify(&headers("authorization", &format!("Basic {wrong}")))),
+            WebhookAuthError::InvalidCredentials
+        ));
+        assert!(matches!(
+            auth_error(verifier.verify(&headers("authorization", &format!("Bearer {token}")))),
+            WebhookAuthError::MalformedCredentials
+        ));
+        assert!(matches!(
+            auth_error(verifier.verify(&headers("authorization", &token))),
+            WebhookAuthError::MalformedCredentials
+        ));
+    }
+
+    #[test]
+    fn rejects_everything_without_secrets() {
+        let verifier = WebhookVerifier::new();
+        assert!(matches!(
+            auth_error(verifier.verify(&headers("x-api-key", "anything"))),
+            WebhookAuthError::MissingCredentials
+        ));
+        assert!(matches!(
+            auth_error(verifier.verify(&HeaderMap::new())),
+            WebhookAuthError::MissingCredentials
+        ));
+    }
+
+    #[test]
+    fn api_key_and_basic_auth_together() {
+        let verifier = WebhookVerifier::api_key("key").with_basic_auth("lava", "password");
+        assert!(verifier.verify(&headers("x-api-key", "key")).is_ok());
+
+        let mut both = headers("x-api-key", "wrong");
+        let token = BASE64.encode("lava:password");
+        both.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Basic {token}")).unwrap(),
+        );
+        assert!(verifier.verify(&both).is_ok());
+    }
+
+    #[test]
+    fn debug_hides_secrets() {
+        let verifier = WebhookVerifier::api_key("super-secret").with_basic_auth("lava", "hunter2");
+        let debug = format!("{verifier:?}");
+        assert!(!debug.contains("super-secret"));
+        assert!(!debug.contains(&BASE64.encode("lava:hunter2")));
+    }
+}
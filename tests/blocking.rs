@@ -0,0 +1,64 @@
+//! Блокирующий клиент против [`MockLavaTop`], запущенного в отдельном runtime.
+#![cfg(all(feature = "blocking", feature = "testing"))]
+
+use lava_top_rs::blocking::LavaTopClient;
+use lava_top_rs::client::WaitPolicy;
+use lava_top_rs::models::common::{CurrencyDto, InvoiceStatus, Periodicity, ProductType};
+use lava_top_rs::models::invoice::{InvoiceRequestDto, ListInvoicesParams};
+use lava_top_rs::models::product::ListProductsParams;
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+use std::time::Duration;
+
+#[test]
+fn blocking_client_against_mock() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let offer = MockOffer::new("Базовый").price(CurrencyDto::Rub, 990.0, Periodicity::OneTime);
+    let offer_id = offer.id;
+    let mut builder = MockLavaTop::builder()
+        .products_page_size(1)
+        .product(MockProduct::new("Курс", ProductType::Course).offer(offer));
+    for n in 0..2 {
+        builder = builder.product(MockProduct::new(format!("Гайд {n}"), ProductType::Guide));
+    }
+    let server = runtime.block_on(builder.start()).unwrap();
+    let client = LavaTopClient::builder()
+        .api_key(server.api_key())
+        .base_url(server.base_url())
+        .build_blocking()
+        .unwrap();
+
+    let ids: Vec<_> = (0..3)
+        .map(|_| {
+            client
+                .create_invoice_v2(&InvoiceRequestDto {
+                    email: "buyer@example.com".to_string(),
+                    offer_id,
+                    ..Default::default()
+                })
+                .unwrap()
+                .id
+        })
+        .collect();
+    runtime.block_on(server.mark_invoice_paid(ids[0])).unwrap();
+
+    let policy = WaitPolicy::new()
+        .interval(Duration::from_millis(10))
+        .timeout(Duration::from_secs(5));
+    let invoice = client.wait_for_invoice(ids[0], &policy).unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Completed);
+
+    let listed = client
+        .invoices_iter(ListInvoicesParams {
+            size: Some(2),
+            ..Default::default()
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(listed.len(), 3);
+
+    let products = client
+        .products_iter(ListProductsParams::default())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(products.len(), 3);
+}
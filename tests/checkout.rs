@@ -0,0 +1,139 @@
+//! Оформление покупок [`Checkout`] по каталогу [`MockLavaTop`].
+#![cfg(feature = "testing")]
+
+use lava_top_rs::checkout::{Checkout, CheckoutRequest, ProductRef};
+use lava_top_rs::error::{FieldIssue, LavaTopError};
+use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+use std::time::Duration;
+
+fn issue(error: LavaTopError) -> FieldIssue {
+    error.validation_errors().unwrap().fields[0]
+        .issue
+        .clone()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn creates_invoice_by_names() {
+    let server = MockLavaTop::builder()
+        .product(
+            MockProduct::new("Курс по Rust", ProductType::Course).offer(
+                MockOffer::new("Базовый")
+                    .price(CurrencyDto::Rub, 990.0, Periodicity::OneTime)
+                    .price(CurrencyDto::Usd, 12.0, Periodicity::OneTime),
+            ),
+        )
+        .product(
+            MockProduct::new("Клуб", ProductType::Subscription).offer(
+                MockOffer::new("Участник")
+                    .price(CurrencyDto::Rub, 490.0, Periodicity::Monthly)
+                    .price(CurrencyDto::Rub, 4900.0, Periodicity::PeriodYear),
+            ),
+        )
+        .start()
+        .await
+        .unwrap();
+    let checkout = Checkout::new(server.client());
+
+    let session = checkout
+        .create(
+            &CheckoutRequest::new("buyer@example.com", " курс по rust ", "базовый")
+                .currency(CurrencyDto::Usd),
+        )
+        .await
+        .unwrap();
+    assert_eq!(session.amount_total.amount, 12.0);
+    assert_eq!(session.expected_amount.unwrap().amount, 12.0);
+    assert!(session.payment_url.is_some());
+    assert_eq!(
+        server.contract(session.contract_id).unwrap().currency,
+        CurrencyDto::Usd
+    );
+
+    let session = checkout
+        .create(
+            &CheckoutRequest::new("buyer@example.com", "Клуб", "Участник")
+                .periodicity(Periodicity::PeriodYear),
+        )
+        .await
+        .unwrap();
+    assert_eq!(session.amount_total.amount, 4900.0);
+    assert_eq!(session.price.periodicity(), Periodicity::PeriodYear);
+
+    let error = checkout
+        .create(&CheckoutRequest::new(
+            "buyer@example.com",
+            "Клуб",
+            "Участник",
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        issue(error),
+        FieldIssue::NoMatchingPrice {
+            periodicity: Periodicity::OneTime,
+            available,
+            ..
+        } if available.len() == 2
+    ));
+}
+
+#[tokio::test]
+async fn catalog_cache_and_invalidate() {
+    let server = MockLavaTop::start().await.unwrap();
+    let checkout = Checkout::new(server.client()).ttl(Duration::from_secs(3600));
+    assert!(checkout.catalog().await.unwrap().is_empty());
+
+    let product = MockProduct::new("Гайд", ProductType::Guide).offer(MockOffer::new("PDF").price(
+        CurrencyDto::Rub,
+        290.0,
+        Periodicity::OneTime,
+    ));
+    let product_id = product.id;
+    server.add_product(product);
+
+    // Кэш еще свежий: перезагрузка по промаху разрешена не чаще раза в 10 секунд
+    let error = checkout
+        .create(&CheckoutRequest::new(
+            "buyer@example.com",
+            product_id,
+            "PDF",
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        issue(error),
+        FieldIssue::ProductNotFound(ProductRef::Id(product_id))
+    );
+
+    checkout.invalidate().await;
+    let session = checkout
+        .create(&CheckoutRequest::new(
+            "buyer@example.com",
+            product_id,
+            "PDF",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(session.price.product_id, product_id);
+}
+
+#[tokio::test]
+async fn rejects_ambiguous_products() {
+    let server = MockLavaTop::builder()
+        .product(MockProduct::new("Курс", ProductType::Course))
+        .product(MockProduct::new("курс", ProductType::Course))
+        .start()
+        .await
+        .unwrap();
+    let error = Checkout::new(server.client())
+        .create(&CheckoutRequest::new(
+            "buyer@example.com",
+            "Курс",
+            "Базовый",
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(issue(error), FieldIssue::AmbiguousProduct(ids) if ids.len() == 2));
+}
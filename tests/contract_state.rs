@@ -0,0 +1,68 @@
+//! Статусы контрактов [`MockLavaTop`] проходят по таблице переходов [`ContractState`].
+#![cfg(feature = "testing")]
+
+use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+use lava_top_rs::models::contract::ContractState;
+use lava_top_rs::models::invoice::InvoiceRequestDto;
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+
+#[tokio::test]
+async fn subscription_lifecycle_follows_transition_table() {
+    let offer = MockOffer::new("Подписка").price(CurrencyDto::Rub, 490.0, Periodicity::Monthly);
+    let offer_id = offer.id;
+    let server = MockLavaTop::builder()
+        .product(MockProduct::new("Клуб", ProductType::Subscription).offer(offer))
+        .start()
+        .await
+        .unwrap();
+    let client = server.client();
+    let parent = client
+        .create_invoice_v2(&InvoiceRequestDto {
+            email: "buyer@example.com".to_string(),
+            offer_id,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .id;
+
+    let state = || ContractState::from(server.contract(parent).unwrap().status);
+    let mut states = vec![state()];
+    server.mark_invoice_paid(parent).await.unwrap();
+    states.push(state());
+    server
+        .fail_subscription_charge(parent, "Недостаточно средств")
+        .await
+        .unwrap();
+    states.push(state());
+    server.charge_subscription(parent).await.unwrap();
+    states.push(state());
+    server.cancel_subscription(parent).await.unwrap();
+    states.push(state());
+
+    assert_eq!(
+        states,
+        [
+            ContractState::New,
+            ContractState::SubscriptionActive,
+            ContractState::SubscriptionFailed,
+            ContractState::SubscriptionActive,
+            ContractState::SubscriptionCancelled,
+        ]
+    );
+    for pair in states.windows(2) {
+        assert!(
+            pair[0].can_transition_to(&pair[1]),
+            "{} -> {}",
+            pair[0],
+            pair[1]
+        );
+    }
+    assert!(states[4].is_paid());
+    assert!(!states[4].can_reach(&ContractState::SubscriptionActive));
+
+    // Статус из `get_invoice_by_id` согласован с состоянием контракта
+    let invoice = client.get_invoice_by_id(&parent).await.unwrap();
+    let invoice_state = ContractState::from(invoice.status);
+    assert!(invoice_state.is_paid() || invoice_state == ContractState::Completed);
+}
@@ -0,0 +1,83 @@
+//! Ошибки разбора успешных ответов: ответы [`MockLavaTop`] портятся слоем клиента.
+#![cfg(feature = "testing")]
+
+use bytes::Bytes;
+use lava_top_rs::client::LavaTopClient;
+use lava_top_rs::error::{LavaTopError, MAX_DECODE_BODY_LEN};
+use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+use lava_top_rs::models::invoice::InvoiceRequestDto;
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+use lava_top_rs::transport::HttpResponse;
+use reqwest::StatusCode;
+use tower::util::MapResponseLayer;
+
+/// Клиент мок-сервера, ответы которого проходят через `corrupt`.
+fn client(server: &MockLavaTop, corrupt: fn(String) -> String) -> LavaTopClient {
+    LavaTopClient::builder()
+        .api_key(server.api_key())
+        .base_url(server.base_url())
+        .layer(MapResponseLayer::new(move |response: HttpResponse| {
+            response.map(|body| Bytes::from(corrupt(String::from_utf8(body.to_vec()).unwrap())))
+        }))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn reports_endpoint_and_field_path() {
+    let offer = MockOffer::new("Базовый").price(CurrencyDto::Rub, 990.0, Periodicity::OneTime);
+    let offer_id = offer.id;
+    let server = MockLavaTop::builder()
+        .product(MockProduct::new("Курс", ProductType::Course).offer(offer))
+        .start()
+        .await
+        .unwrap();
+    let invoice = server
+        .client()
+        .create_invoice_v2(&InvoiceRequestDto {
+            email: "buyer@example.com".to_string(),
+            offer_id,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let client = client(&server, |body| {
+        body.replace(r#""amount":990.0"#, r#""amount":"990""#)
+    });
+    let error = client.get_invoice_by_id(&invoice.id).await.unwrap_err();
+    let LavaTopError::Decode {
+        endpoint,
+        status,
+        path,
+        body,
+        ..
+    } = &error
+    else {
+        panic!("ожидалась ошибка Decode, получено {error:?}");
+    };
+    assert_eq!(endpoint, &format!("/api/v1/invoices/{}", invoice.id));
+    assert_eq!(*status, StatusCode::OK);
+    assert_eq!(path, "receipt.amount");
+    assert!(body.contains(r#""amount":"990""#));
+    assert!(error.to_string().contains("receipt.amount"));
+}
+
+#[tokio::test]
+async fn caps_large_bodies() {
+    let server = MockLavaTop::start().await.unwrap();
+    let client = client(&server, |body| {
+        let padding = "x".repeat(2 * MAX_DECODE_BODY_LEN);
+        body.replace(
+            r#""nextPage":null"#,
+            &format!(r#""padding":"{padding}","nextPage":7"#),
+        )
+    });
+    let error = client.list_products_v2(None).await.unwrap_err();
+    let LavaTopError::Decode { path, body, .. } = error else {
+        panic!("ожидалась ошибка Decode, получено {error:?}");
+    };
+    assert_eq!(path, "nextPage");
+    assert!(body.len() < MAX_DECODE_BODY_LEN + 32);
+    assert!(body.ends_with(" B)"));
+}
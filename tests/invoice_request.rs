@@ -0,0 +1,51 @@
+//! Проверенные запросы [`InvoiceRequest`] по офферам из каталога [`MockLavaTop`].
+#![cfg(feature = "testing")]
+
+use lava_top_rs::error::FieldIssue;
+use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+use lava_top_rs::models::invoice::InvoiceRequest;
+use lava_top_rs::models::product::{FeedData, ListProductsParams};
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+
+#[tokio::test]
+async fn validates_against_catalog_offer() {
+    let offer = MockOffer::new("Подписка")
+        .price(CurrencyDto::Rub, 490.0, Periodicity::Monthly)
+        .price(CurrencyDto::Rub, 4900.0, Periodicity::PeriodYear);
+    let server = MockLavaTop::builder()
+        .product(MockProduct::new("Клуб", ProductType::Subscription).offer(offer))
+        .start()
+        .await
+        .unwrap();
+    let client = server.client();
+
+    // Годовые цены подписок API показывает только по запросу
+    let params = ListProductsParams {
+        show_all_subscription_periods: Some(true),
+        ..Default::default()
+    };
+    let page = client.list_products_v2(Some(&params)).await.unwrap();
+    let FeedData::Product(product) = &page.items[0].data else {
+        panic!("ожидался продукт");
+    };
+    let offer = &product.offers[0];
+
+    let error = InvoiceRequest::builder("buyer@example.com", offer.id)
+        .offer(offer)
+        .build()
+        .unwrap_err();
+    let fields = error.validation_errors().unwrap().fields;
+    assert_eq!(fields[0].issue, Some(FieldIssue::SubscriptionOnly));
+
+    let request = InvoiceRequest::builder("buyer@example.com", offer.id)
+        .offer(offer)
+        .periodicity(Periodicity::PeriodYear)
+        .build()
+        .unwrap();
+    let invoice = client.create_invoice_v2(&request).await.unwrap();
+    assert_eq!(invoice.amount_total.amount, 4900.0);
+    assert_eq!(
+        server.contract(invoice.id).unwrap().periodicity,
+        Periodicity::PeriodYear
+    );
+}
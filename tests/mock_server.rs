@@ -0,0 +1,236 @@
+//! Сценарии клиента против [`MockLavaTop`].
+#![cfg(feature = "testing")]
+
+use lava_top_rs::client::LavaTopClient;
+use lava_top_rs::error::LavaTopError;
+use lava_top_rs::models::common::{
+    ContractStatusDto, CurrencyDto, InvoiceStatus, Periodicity, ProductType, SubscriptionStatus,
+};
+use lava_top_rs::models::invoice::{InvoiceRequestDto, ListInvoicesParams};
+use lava_top_rs::models::subscription::CancelSubscriptionParams;
+use lava_top_rs::testing::{MockError, MockLavaTop, MockOffer, MockProduct};
+use reqwest::StatusCode;
+use url::Url;
+use uuid::Uuid;
+
+async fn server(periodicity: Periodicity) -> (MockLavaTop, Uuid) {
+    let offer = MockOffer::new("Базовый").price(CurrencyDto::Rub, 990.0, periodicity);
+    let offer_id = offer.id;
+    let server = MockLavaTop::builder()
+        .product(MockProduct::new("Курс", ProductType::Course).offer(offer))
+        .start()
+        .await
+        .unwrap();
+    (server, offer_id)
+}
+
+fn request(email: &str, offer_id: Uuid) -> InvoiceRequestDto {
+    InvoiceRequestDto {
+        email: email.to_string(),
+        offer_id,
+        ..Default::default()
+    }
+}
+
+fn api_status(error: LavaTopError) -> StatusCode {
+    match error {
+        LavaTopError::ApiError { status, .. } => status,
+        other => panic!("ожидалась ошибка API, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn invoice_lifecycle() {
+    let (server, offer_id) = server(Periodicity::OneTime).await;
+    let client = server.client();
+
+    let created = client
+        .create_invoice_v2(&request("buyer@example.com", offer_id))
+        .await
+        .unwrap();
+    let invoice = client.get_invoice_by_id(&created.id).await.unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::New);
+
+    server.mark_invoice_paid(created.id).await.unwrap();
+    let invoice = client.get_invoice_by_id(&created.id).await.unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Completed);
+    assert_eq!(
+        server.contract(created.id).unwrap().status,
+        ContractStatusDto::Completed
+    );
+
+    let error = server
+        .mark_invoice_failed(created.id, "поздно")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MockError::InvalidState {
+            action: "mark_invoice_failed",
+            status: ContractStatusDto::Completed,
+            ..
+        }
+    ));
+
+    let failed = client
+        .create_invoice_v2(&request("buyer@example.com", offer_id))
+        .await
+        .unwrap();
+    server
+        .mark_invoice_failed(failed.id, "Недостаточно средств")
+        .await
+        .unwrap();
+    let contract = server.contract(failed.id).unwrap();
+    assert_eq!(contract.status, ContractStatusDto::Failed);
+    assert_eq!(
+        contract.error_message.as_deref(),
+        Some("Недостаточно средств")
+    );
+    assert!(matches!(
+        server.mark_invoice_paid(failed.id).await,
+        Err(MockError::InvalidState {
+            action: "mark_invoice_paid",
+            ..
+        })
+    ));
+
+    let unknown = Uuid::new_v4();
+    assert!(matches!(
+        server.mark_invoice_paid(unknown).await,
+        Err(MockError::ContractNotFound(id)) if id == unknown
+    ));
+}
+
+#[tokio::test]
+async fn api_errors() {
+    let (server, offer_id) = server(Periodicity::OneTime).await;
+    let client = server.client();
+
+    let error = client
+        .create_invoice_v2(&request("buyer@example.com", Uuid::new_v4()))
+        .await
+        .unwrap_err();
+    assert_eq!(api_status(error), StatusCode::NOT_FOUND);
+
+    let error = client
+        .create_invoice_v2(&InvoiceRequestDto {
+            currency: CurrencyDto::Usd,
+            ..request("buyer@example.com", offer_id)
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(api_status(error), StatusCode::BAD_REQUEST);
+
+    let error = client.get_invoice_by_id(&Uuid::new_v4()).await.unwrap_err();
+    assert_eq!(api_status(error), StatusCode::NOT_FOUND);
+
+    let stranger = LavaTopClient::builder()
+        .api_key("wrong-key")
+        .base_url(server.base_url())
+        .build()
+        .unwrap();
+    let error = stranger.list_invoices(None).await.unwrap_err();
+    assert_eq!(api_status(error), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn invoice_paging_and_filters() {
+    let (server, offer_id) = server(Periodicity::OneTime).await;
+    let client = server.client();
+    for email in ["a@example.com", "b@example.com", "a@example.com"] {
+        client
+            .create_invoice_v2(&request(email, offer_id))
+            .await
+            .unwrap();
+    }
+
+    let page = client
+        .list_invoices(Some(&ListInvoicesParams {
+            buyer_email: Some("A@example.com".to_string()),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert_eq!(page.total, 2);
+
+    let page = client
+        .list_invoices(Some(&ListInvoicesParams {
+            page: Some(i64::MAX),
+            size: Some(100),
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+    assert_eq!(page.total, 3);
+
+    for (page, size) in [(0, 10), (1, 0), (1, 101)] {
+        let error = client
+            .list_invoices(Some(&ListInvoicesParams {
+                page: Some(page),
+                size: Some(size),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            api_status(error),
+            StatusCode::BAD_REQUEST,
+            "page={page}, size={size}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn subscription_charges_and_cancellation() {
+    let (server, offer_id) = server(Periodicity::Monthly).await;
+    let client = server.client();
+    let parent = client
+        .create_invoice_v2(&request("buyer@example.com", offer_id))
+        .await
+        .unwrap()
+        .id;
+    assert!(matches!(
+        server.charge_subscription(parent).await,
+        Err(MockError::InvalidState {
+            action: "charge_subscription",
+            ..
+        })
+    ));
+    server.mark_invoice_paid(parent).await.unwrap();
+
+    let charge = server.charge_subscription(parent).await.unwrap();
+    let invoice = client.get_invoice_by_id(&charge).await.unwrap();
+    assert_eq!(invoice.parent_invoice.map(|p| p.id), Some(parent));
+
+    let error = client
+        .cancel_subscription(&CancelSubscriptionParams {
+            contract_id: parent,
+            email: "other@example.com".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(api_status(error), StatusCode::NOT_FOUND);
+
+    client
+        .cancel_subscription(&CancelSubscriptionParams {
+            contract_id: parent,
+            email: "buyer@example.com".to_string(),
+        })
+        .await
+        .unwrap();
+    let contract = server.contract(parent).unwrap();
+    assert_eq!(
+        contract.subscription_status,
+        Some(SubscriptionStatus::Cancelled)
+    );
+    assert!(contract.will_expire_at.is_some());
+}
+
+#[tokio::test]
+async fn donate_link() {
+    let server = MockLavaTop::start().await.unwrap();
+    let url = Url::parse("https://app.lava.top/donate/test").unwrap();
+    server.set_donate_url(url.clone());
+    assert_eq!(server.client().get_donate_link().await.unwrap().url, url);
+}
@@ -0,0 +1,63 @@
+//! Суммы [`Money`] в ответах и запросах [`MockLavaTop`].
+#![cfg(all(feature = "money", feature = "testing"))]
+
+use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+use lava_top_rs::models::money::Money;
+use lava_top_rs::models::product::{
+    FeedData, ProductItemResponse, ProductUpdateRequest, UpdateOfferRequest, UpdatePriceRequest,
+};
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+#[tokio::test]
+async fn prices_round_trip_through_api() {
+    let offer = MockOffer::new("Базовый")
+        .price(CurrencyDto::Rub, 990.1, Periodicity::OneTime)
+        .price(CurrencyDto::Usd, 10.99, Periodicity::OneTime);
+    let offer_id = offer.id;
+    let product = MockProduct::new("Курс", ProductType::Course).offer(offer);
+    let product_id = product.id;
+    let server = MockLavaTop::builder()
+        .product(product)
+        .start()
+        .await
+        .unwrap();
+    let client = server.client();
+
+    let price = |product: &ProductItemResponse| {
+        product.offers[0]
+            .prices
+            .iter()
+            .find(|price| price.currency == CurrencyDto::Rub)
+            .and_then(|price| price.money().unwrap())
+            .unwrap()
+    };
+    let page = client.list_products_v2(None).await.unwrap();
+    let FeedData::Product(product) = &page.items[0].data else {
+        panic!("ожидался продукт");
+    };
+    let rub = price(product);
+    assert_eq!(rub, Money::from_minor_units(99010, CurrencyDto::Rub));
+
+    let raised = rub
+        .checked_mul(Decimal::from_str("1.1").unwrap())
+        .unwrap()
+        .round_to_minor_units();
+    assert_eq!(raised.to_string(), "1089.11 RUB");
+    let updated = client
+        .update_product(
+            product_id,
+            &ProductUpdateRequest {
+                offers: Some(vec![UpdateOfferRequest {
+                    id: offer_id,
+                    prices: Some(vec![UpdatePriceRequest::try_from(&raised).unwrap()]),
+                    name: None,
+                    description: None,
+                }]),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(price(&updated), raised);
+}
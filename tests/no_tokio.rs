@@ -0,0 +1,131 @@
+//! Клиент без tokio: однопоточный исполнитель `futures` и свой транспорт поверх
+//! `std::net`. Запускается с `cargo test --no-default-features`.
+#![cfg(not(feature = "tokio"))]
+
+use bytes::Bytes;
+use futures::executor::block_on;
+use lava_top_rs::client::LavaTopClient;
+use lava_top_rs::retry::RetryPolicy;
+use lava_top_rs::transport::{HttpRequest, HttpResponse};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Локальный HTTP-сервер: отвечает заранее заданными ответами по одному на соединение
+/// и пересылает строку запроса и заголовок `X-Api-Key`.
+fn serve(responses: Vec<(u16, &'static str)>) -> (SocketAddr, mpsc::Receiver<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut api_key = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("x-api-key")
+                {
+                    api_key = value.trim().to_string();
+                }
+            }
+            sender
+                .send((request_line.trim().to_string(), api_key))
+                .unwrap();
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+    });
+    (addr, receiver)
+}
+
+/// Транспорт на блокирующем `std::net::TcpStream` (HTTP/1.1, `Connection: close`).
+fn send(addr: SocketAddr, request: &HttpRequest) -> io::Result<HttpResponse> {
+    let mut stream = TcpStream::connect(addr)?;
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path| path.as_str());
+    write!(
+        stream,
+        "{} {path} HTTP/1.1\r\nHost: {addr}\r\n",
+        request.method()
+    )?;
+    for (name, value) in request.headers() {
+        write!(stream, "{name}: ")?;
+        stream.write_all(value.as_bytes())?;
+        stream.write_all(b"\r\n")?;
+    }
+    write!(
+        stream,
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        request.body().len()
+    )?;
+    stream.write_all(request.body())?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw)?;
+    let split = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| io::Error::other("нет конца заголовков"))?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let status: u16 = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| io::Error::other("нет статуса"))?;
+    http::Response::builder()
+        .status(status)
+        .body(Bytes::copy_from_slice(&raw[split + 4..]))
+        .map_err(io::Error::other)
+}
+
+#[test]
+fn retries_on_futures_executor_without_tokio() {
+    let (addr, requests) = serve(vec![
+        (503, ""),
+        (200, r#"{"url":"https://lava.top/donate/example"}"#),
+    ]);
+    let transport =
+        tower::service_fn(move |request: HttpRequest| async move { send(addr, &request) });
+    let client = LavaTopClient::builder()
+        .api_key("no-tokio-key")
+        .base_url(Url::parse(&format!("http://{addr}/")).unwrap())
+        .transport(transport)
+        .timeout(Duration::from_secs(5))
+        .retry_policy(
+            RetryPolicy::new()
+                .initial_backoff(Duration::from_millis(50))
+                .jitter(false),
+        )
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    let donate = block_on(client.get_donate_link()).unwrap();
+
+    assert_eq!(donate.url.as_str(), "https://lava.top/donate/example");
+    // Повтор ждал на таймере futures-timer, а не завершился сразу
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    for _ in 0..2 {
+        let (request_line, api_key) = requests.recv().unwrap();
+        assert_eq!(request_line, "GET /api/v1/donate HTTP/1.1");
+        assert_eq!(api_key, "no-tokio-key");
+    }
+}
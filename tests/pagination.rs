@@ -0,0 +1,105 @@
+//! Потоки страниц против [`MockLavaTop`].
+#![cfg(feature = "testing")]
+
+use futures::TryStreamExt;
+use lava_top_rs::error::LavaTopError;
+use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+use lava_top_rs::models::invoice::{InvoiceRequestDto, ListInvoicesParams};
+use lava_top_rs::models::product::{FeedData, FeedItemCombined, ListProductsParams};
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+use url::Url;
+use uuid::Uuid;
+
+fn product(title: &str) -> MockProduct {
+    MockProduct::new(title, ProductType::Course).offer(MockOffer::new("Базовый").price(
+        CurrencyDto::Rub,
+        990.0,
+        Periodicity::OneTime,
+    ))
+}
+
+#[tokio::test]
+async fn invoices_stream_walks_all_pages() {
+    let course = product("Курс");
+    let offer_id = course.offers[0].id;
+    let server = MockLavaTop::builder()
+        .product(course)
+        .start()
+        .await
+        .unwrap();
+    let client = server.client();
+
+    let mut created = Vec::new();
+    for n in 0..5 {
+        let invoice = client
+            .create_invoice_v2(&InvoiceRequestDto {
+                email: format!("buyer{n}@example.com"),
+                offer_id,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        created.push(invoice.id);
+    }
+
+    let mut ids: Vec<Uuid> = client
+        .invoices_stream(ListInvoicesParams {
+            size: Some(2),
+            ..Default::default()
+        })
+        .map_ok(|invoice| invoice.id)
+        .try_collect()
+        .await
+        .unwrap();
+    ids.sort();
+    created.sort();
+    assert_eq!(ids, created);
+
+    let tail: Vec<_> = client
+        .invoices_stream(ListInvoicesParams {
+            page: Some(3),
+            size: Some(2),
+            ..Default::default()
+        })
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(tail.len(), 1);
+}
+
+#[tokio::test]
+async fn products_stream_follows_next_page() {
+    let mut builder = MockLavaTop::builder().products_page_size(2);
+    for n in 0..5 {
+        builder = builder.product(product(&format!("Курс {n}")));
+    }
+    let server = builder.start().await.unwrap();
+
+    let items: Vec<FeedItemCombined> = server
+        .client()
+        .products_stream(ListProductsParams::default())
+        .try_collect()
+        .await
+        .unwrap();
+    let mut titles: Vec<String> = items
+        .into_iter()
+        .filter_map(|item| match item.data {
+            FeedData::Product(product) => product.title,
+            FeedData::Post(_) => None,
+        })
+        .collect();
+    titles.sort();
+    assert_eq!(titles, ["Курс 0", "Курс 1", "Курс 2", "Курс 3", "Курс 4"]);
+}
+
+#[tokio::test]
+async fn fetch_next_page_rejects_foreign_origin() {
+    let server = MockLavaTop::start().await.unwrap();
+    let foreign = Url::parse("https://evil.example.com/api/v2/products?cursor=2").unwrap();
+    let error = server
+        .client()
+        .fetch_next_page::<FeedItemCombined>(&foreign)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, LavaTopError::ForeignNextPage(url) if url == foreign));
+}
@@ -0,0 +1,98 @@
+//! Квоты [`RateLimiter`] по группам эндпоинтов и паузы по ответу 429.
+#![cfg(feature = "testing")]
+
+use bytes::Bytes;
+use lava_top_rs::client::LavaTopClient;
+use lava_top_rs::error::{BoxError, LavaTopError};
+use lava_top_rs::rate_limit::{EndpointGroup, Quota, RateLimiter};
+use lava_top_rs::retry::RetryPolicy;
+use lava_top_rs::testing::MockLavaTop;
+use lava_top_rs::transport::{HttpRequest, HttpResponse};
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use url::Url;
+
+const INTERVAL: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn quota_applies_per_endpoint_group() {
+    let server = MockLavaTop::start().await.unwrap();
+    let limiter =
+        RateLimiter::new().quota(EndpointGroup::Invoices, Quota::per(1, INTERVAL).burst(1));
+    let client = LavaTopClient::builder()
+        .api_key(server.api_key())
+        .base_url(server.base_url())
+        .rate_limiter(limiter)
+        .build()
+        .unwrap();
+
+    // Продукты и донаты не ограничены
+    let start = Instant::now();
+    for _ in 0..3 {
+        client.list_products_v2(None).await.unwrap();
+        let _ = client.get_donate_link().await;
+    }
+    assert!(start.elapsed() < INTERVAL, "{:?}", start.elapsed());
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        client.list_invoices(None).await.unwrap();
+    }
+    assert!(start.elapsed() >= INTERVAL * 2, "{:?}", start.elapsed());
+}
+
+#[tokio::test]
+async fn too_many_requests_pauses_group() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let transport = tower::service_fn(move |request: HttpRequest| {
+        let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+        async move {
+            let response = if first {
+                assert_eq!(request.extensions().get(), Some(&EndpointGroup::Sales));
+                http::Response::builder()
+                    .status(StatusCode::TOO_MANY_REQUESTS)
+                    .header(reqwest::header::RETRY_AFTER, "30")
+                    .body(Bytes::new())
+            } else {
+                http::Response::builder()
+                    .status(StatusCode::OK)
+                    .body(Bytes::from_static(
+                        br#"{"items":[],"page":1,"size":20,"total":0,"totalPages":0}"#,
+                    ))
+            };
+            Ok::<HttpResponse, BoxError>(response.unwrap())
+        }
+    });
+    let client = LavaTopClient::builder()
+        .api_key("test-api-key")
+        .base_url(Url::parse("http://lava.test").unwrap())
+        .transport(transport)
+        .retry_policy(RetryPolicy::disabled())
+        .rate_limiter(RateLimiter::new().max_pause(INTERVAL))
+        .build()
+        .unwrap();
+
+    let error = client.list_partner_sales(None).await.unwrap_err();
+    assert!(matches!(
+        error,
+        LavaTopError::ApiError {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            retry_after: Some(retry_after),
+            ..
+        } if retry_after == Duration::from_secs(30)
+    ));
+
+    // Другие группы не ждут
+    let start = Instant::now();
+    client.list_invoices(None).await.unwrap();
+    assert!(start.elapsed() < INTERVAL / 2, "{:?}", start.elapsed());
+
+    // Группа продаж ждет паузу, сокращенную до `max_pause`
+    client.list_partner_sales(None).await.unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= INTERVAL * 8 / 10, "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(5), "{elapsed:?}");
+}
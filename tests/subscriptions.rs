@@ -0,0 +1,90 @@
+//! [`SubscriptionTracker`] по контрактам и вебхукам [`MockLavaTop`].
+#![cfg(feature = "testing")]
+
+use lava_top_rs::models::common::{
+    ContractStatusDto, CurrencyDto, InvoiceType, Periodicity, ProductType,
+};
+use lava_top_rs::models::contract::ContractState;
+use lava_top_rs::models::invoice::{InvoiceRequestDto, ListInvoicesParams};
+use lava_top_rs::subscriptions::SubscriptionTracker;
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+
+fn recurring() -> ListInvoicesParams {
+    ListInvoicesParams {
+        invoice_types: Some(vec![InvoiceType::Recurring]),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn tracks_charges_and_cancellation() {
+    let subscription =
+        MockOffer::new("Подписка").price(CurrencyDto::Rub, 490.0, Periodicity::Monthly);
+    let one_time = MockOffer::new("Разовый").price(CurrencyDto::Rub, 990.0, Periodicity::OneTime);
+    let (subscription_id, one_time_id) = (subscription.id, one_time.id);
+    let server = MockLavaTop::builder()
+        .product(MockProduct::new("Клуб", ProductType::Subscription).offer(subscription))
+        .product(MockProduct::new("Курс", ProductType::Course).offer(one_time))
+        .start()
+        .await
+        .unwrap();
+    let client = server.client();
+    let mut ids = Vec::new();
+    for offer_id in [subscription_id, one_time_id] {
+        let id = client
+            .create_invoice_v2(&InvoiceRequestDto {
+                email: "buyer@example.com".to_string(),
+                offer_id,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .id;
+        server.mark_invoice_paid(id).await.unwrap();
+        ids.push(id);
+    }
+    let parent = ids[0];
+    server.charge_subscription(parent).await.unwrap();
+    server
+        .fail_subscription_charge(parent, "Недостаточно средств")
+        .await
+        .unwrap();
+
+    let tracker = SubscriptionTracker::load(&client, ListInvoicesParams::default())
+        .await
+        .unwrap();
+    assert_eq!(tracker.len(), 1);
+    let tracked = tracker.get(&parent).unwrap();
+    assert_eq!(tracked.state(), &ContractState::SubscriptionFailed);
+    assert_eq!(tracked.payments().len(), 3);
+    assert_eq!(tracked.failed_payment_count(), 1);
+    assert_eq!(tracked.email(), Some("buyer@example.com"));
+
+    server.charge_subscription(parent).await.unwrap();
+    let mut tracker = SubscriptionTracker::load(&client, recurring())
+        .await
+        .unwrap();
+    let tracked = tracker.get_mut(&parent).unwrap();
+    assert_eq!(tracked.state(), &ContractState::SubscriptionActive);
+    assert_eq!(tracked.payments().len(), 4);
+    assert_eq!(tracked.next_charge_at(), None);
+    tracked.set_periodicity(Periodicity::Monthly);
+    let next_charge = tracked.next_charge_at().unwrap();
+    assert!(next_charge > tracked.last_payment().unwrap().timestamp);
+
+    tracked.cancel(&client).await.unwrap();
+    assert_eq!(tracked.state(), &ContractState::SubscriptionCancelled);
+    assert_eq!(tracked.next_charge_at(), None);
+    assert_eq!(
+        server.contract(parent).unwrap().status,
+        ContractStatusDto::SubscriptionCancelled
+    );
+
+    let tracker = SubscriptionTracker::load(&client, recurring())
+        .await
+        .unwrap();
+    let tracked = tracker.get(&parent).unwrap();
+    assert_eq!(tracked.state(), &ContractState::SubscriptionCancelled);
+    assert!(tracked.cancelled_at().is_some());
+    assert!(tracked.will_expire_at().is_some());
+}
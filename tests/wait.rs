@@ -0,0 +1,150 @@
+//! Ожидание оплаты контракта против [`MockLavaTop`].
+#![cfg(feature = "testing")]
+
+use futures::StreamExt;
+use futures::channel::mpsc;
+use lava_top_rs::client::{WaitOutcome, WaitPolicy};
+use lava_top_rs::error::LavaTopError;
+use lava_top_rs::models::common::{CurrencyDto, InvoiceStatus, Periodicity, ProductType};
+use lava_top_rs::models::invoice::InvoiceRequestDto;
+use lava_top_rs::models::webhook::WebhookEvent;
+use lava_top_rs::testing::{MockLavaTop, MockOffer, MockProduct};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Интервал опроса, который тесты с вебхуками не должны дожидаться.
+const SLOW: Duration = Duration::from_secs(30);
+
+async fn server_with_invoice() -> (Arc<MockLavaTop>, Uuid) {
+    let offer = MockOffer::new("Базовый").price(CurrencyDto::Rub, 990.0, Periodicity::OneTime);
+    let offer_id = offer.id;
+    let server = MockLavaTop::builder()
+        .product(MockProduct::new("Курс", ProductType::Course).offer(offer))
+        .start()
+        .await
+        .unwrap();
+    let id = server
+        .client()
+        .create_invoice_v2(&InvoiceRequestDto {
+            email: "buyer@example.com".to_string(),
+            offer_id,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .id;
+    (Arc::new(server), id)
+}
+
+/// Вебхук о платеже по контракту `id`, который мок-сервер не отправлял.
+fn payment(event_type: &str, id: Uuid) -> WebhookEvent {
+    serde_json::from_value(serde_json::json!({
+        "eventType": event_type,
+        "product": { "id": Uuid::new_v4(), "title": "Курс" },
+        "contractId": id,
+        "buyer": { "email": "buyer@example.com" },
+        "amount": 990.0,
+        "currency": "RUB",
+        "status": if event_type == "payment_success" { "completed" } else { "failed" },
+        "timestamp": "2024-02-05T09:38:27Z",
+    }))
+    .unwrap()
+}
+
+fn fast() -> WaitPolicy {
+    WaitPolicy::new()
+        .interval(Duration::from_millis(10))
+        .max_interval(Duration::from_millis(20))
+}
+
+#[tokio::test]
+async fn completes_after_payment() {
+    let (server, id) = server_with_invoice().await;
+    let payer = server.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        payer.mark_invoice_paid(id).await.unwrap();
+    });
+
+    let statuses: Vec<InvoiceStatus> = server
+        .client()
+        .invoice_status_stream(id, &fast().timeout(Duration::from_secs(5)))
+        .map(|invoice| invoice.unwrap().status)
+        .collect()
+        .await;
+    assert_eq!(statuses, [InvoiceStatus::New, InvoiceStatus::Completed]);
+}
+
+#[tokio::test]
+async fn times_out_with_last_status() {
+    let (server, id) = server_with_invoice().await;
+    let start = Instant::now();
+    let error = server
+        .client()
+        .wait_for_invoice(id, &fast().timeout(Duration::from_millis(80)))
+        .await
+        .unwrap_err();
+    assert!(start.elapsed() >= Duration::from_millis(80));
+    assert!(matches!(
+        error,
+        LavaTopError::InvoiceWaitTimeout {
+            invoice_id,
+            last_status: Some(InvoiceStatus::New),
+        } if invoice_id == id
+    ));
+}
+
+#[tokio::test]
+async fn success_webhook_ends_wait_before_api() {
+    let (server, id) = server_with_invoice().await;
+    let (sender, webhooks) = mpsc::unbounded();
+    sender
+        .unbounded_send(payment("payment_success", Uuid::new_v4()))
+        .unwrap();
+    sender
+        .unbounded_send(payment("payment_success", id))
+        .unwrap();
+
+    let start = Instant::now();
+    let outcome = server
+        .client()
+        .wait_for_invoice_with_webhooks(id, &WaitPolicy::new().interval(SLOW), webhooks)
+        .await
+        .unwrap();
+    assert!(start.elapsed() < SLOW);
+    assert!(matches!(&outcome, WaitOutcome::Webhook(event) if event.contract_id() == id));
+    assert!(outcome.is_paid());
+    // API по-прежнему не знает об оплате, и ожидание его не изменило
+    assert_eq!(
+        server.client().get_invoice_by_id(&id).await.unwrap().status,
+        InvoiceStatus::New
+    );
+}
+
+#[tokio::test]
+async fn failed_webhook_does_not_end_wait() {
+    let (server, id) = server_with_invoice().await;
+    let (sender, webhooks) = mpsc::unbounded();
+    let client = server.client();
+    let policy = WaitPolicy::new().interval(SLOW);
+    let mut outcomes = Box::pin(client.invoice_status_stream_with_webhooks(id, &policy, webhooks));
+
+    let first = outcomes.next().await.unwrap().unwrap();
+    assert_eq!(first.invoice().unwrap().status, InvoiceStatus::New);
+
+    sender
+        .unbounded_send(payment("payment_failed", id))
+        .unwrap();
+    let pending = tokio::time::timeout(Duration::from_millis(200), outcomes.next()).await;
+    assert!(pending.is_err(), "неуспешный платеж завершил ожидание");
+
+    // Покупатель оплатил со второй попытки
+    server.mark_invoice_paid(id).await.unwrap();
+    sender
+        .unbounded_send(payment("payment_success", id))
+        .unwrap();
+    let outcome = outcomes.next().await.unwrap().unwrap();
+    assert_eq!(outcome.invoice().unwrap().status, InvoiceStatus::Completed);
+    assert!(outcomes.next().await.is_none());
+}
@@ -0,0 +1,136 @@
+//! Доставка вебхуков из [`MockLavaTop`] в [`WebhookReceiver`] через axum.
+#![cfg(feature = "testing")]
+
+use lava_top_rs::models::common::{CurrencyDto, Periodicity, ProductType};
+use lava_top_rs::models::invoice::InvoiceRequestDto;
+use lava_top_rs::models::webhook::WebhookEvent;
+use lava_top_rs::testing::{MockError, MockLavaTop, MockOffer, MockProduct};
+use lava_top_rs::webhook::{WebhookReceiver, WebhookVerifier};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use url::Url;
+use uuid::Uuid;
+
+const WEBHOOK_KEY: &str = "webhook-key";
+
+/// Запускает приемник вебхуков и возвращает его адрес и канал полученных событий.
+async fn receiver() -> (Url, mpsc::UnboundedReceiver<WebhookEvent>) {
+    let (sender, events) = mpsc::unbounded_channel();
+    let router = WebhookReceiver::new(WebhookVerifier::api_key(WEBHOOK_KEY), move |event| {
+        let sender = sender.clone();
+        async move {
+            sender.send(event)?;
+            Ok(())
+        }
+    })
+    .into_axum_router("/webhook");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!(
+        "http://{}/webhook",
+        listener.local_addr().unwrap()
+    ))
+    .unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    (url, events)
+}
+
+async fn server(url: Url, api_key: &str, periodicity: Periodicity) -> (MockLavaTop, Uuid) {
+    let offer = MockOffer::new("Базовый").price(CurrencyDto::Rub, 990.0, periodicity);
+    let offer_id = offer.id;
+    let server = MockLavaTop::builder()
+        .webhook(url, Some(api_key.to_string()))
+        .product(MockProduct::new("Курс", ProductType::Course).offer(offer))
+        .start()
+        .await
+        .unwrap();
+    (server, offer_id)
+}
+
+async fn invoice(server: &MockLavaTop, offer_id: Uuid) -> Uuid {
+    server
+        .client()
+        .create_invoice_v2(&InvoiceRequestDto {
+            email: "buyer@example.com".to_string(),
+            offer_id,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .id
+}
+
+#[tokio::test]
+async fn delivers_payment_events() {
+    let (url, mut events) = receiver().await;
+    let (server, offer_id) = server(url, WEBHOOK_KEY, Periodicity::OneTime).await;
+
+    let paid = invoice(&server, offer_id).await;
+    server.mark_invoice_paid(paid).await.unwrap();
+    match events.recv().await.unwrap() {
+        WebhookEvent::PaymentSuccess(payment) => {
+            assert_eq!(payment.contract_id, paid);
+            assert_eq!(payment.buyer.email, "buyer@example.com");
+        }
+        other => panic!("ожидалось payment_success, получено {other:?}"),
+    }
+
+    let failed = invoice(&server, offer_id).await;
+    server
+        .mark_invoice_failed(failed, "Недостаточно средств")
+        .await
+        .unwrap();
+    match events.recv().await.unwrap() {
+        WebhookEvent::PaymentFailed(payment) => {
+            assert_eq!(payment.contract_id, failed);
+            assert_eq!(
+                payment.error_message.as_deref(),
+                Some("Недостаточно средств")
+            );
+        }
+        other => panic!("ожидалось payment_failed, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn delivers_subscription_events() {
+    let (url, mut events) = receiver().await;
+    let (server, offer_id) = server(url, WEBHOOK_KEY, Periodicity::Monthly).await;
+
+    let parent = invoice(&server, offer_id).await;
+    server.mark_invoice_paid(parent).await.unwrap();
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        WebhookEvent::PaymentSuccess(_)
+    ));
+
+    let charge = server.charge_subscription(parent).await.unwrap();
+    match events.recv().await.unwrap() {
+        WebhookEvent::SubscriptionRecurringPaymentSuccess(recurring) => {
+            assert_eq!(recurring.parent_contract_id, parent);
+            assert_eq!(recurring.payment.contract_id, charge);
+        }
+        other => panic!("ожидалось recurring_payment_success, получено {other:?}"),
+    }
+
+    server.cancel_subscription(parent).await.unwrap();
+    match events.recv().await.unwrap() {
+        WebhookEvent::SubscriptionCancelled(cancelled) => {
+            assert_eq!(cancelled.contract_id, parent);
+            assert!(cancelled.will_expire_at >= cancelled.cancelled_at);
+        }
+        other => panic!("ожидалось subscription_cancelled, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn rejected_webhook_is_reported() {
+    let (url, mut events) = receiver().await;
+    let (server, offer_id) = server(url, "wrong-key", Periodicity::OneTime).await;
+
+    let id = invoice(&server, offer_id).await;
+    let error = server.mark_invoice_paid(id).await.unwrap_err();
+    assert!(
+        matches!(&error, MockError::Webhook(e) if e.status() == Some(reqwest::StatusCode::UNAUTHORIZED))
+    );
+    assert!(events.try_recv().is_err());
+}